@@ -0,0 +1,49 @@
+use advent_of_code_2023_day_02::GameParser;
+use std::{hint::black_box, time::Instant};
+
+const NUMBER_OF_GAMES: u32 = 2_000_000;
+
+fn main() {
+    let input = get_input(NUMBER_OF_GAMES);
+
+    let mut game_parser = GameParser::new();
+
+    let start = Instant::now();
+    for line in input.lines() {
+        match game_parser.try_parse(line) {
+            Ok(game) => {
+                black_box(game);
+            }
+            Err(game_error) => panic!("benchmark input should be valid, {}", game_error),
+        }
+    }
+    let elapsed = start.elapsed();
+
+    println!(
+        "Parsed {} games in {:?} ({:.0} games/s)",
+        NUMBER_OF_GAMES,
+        elapsed,
+        NUMBER_OF_GAMES as f64 / elapsed.as_secs_f64()
+    );
+}
+
+fn get_input(number_of_games: u32) -> String {
+    let subsets = [
+        "3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+        "1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+        "8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+        "1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
+        "6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+    ];
+
+    let mut input = String::new();
+    for game_id in 1..=number_of_games {
+        input.push_str(&format!(
+            "Game {}: {}\n",
+            game_id,
+            subsets[game_id as usize % subsets.len()]
+        ));
+    }
+
+    input
+}
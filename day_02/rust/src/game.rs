@@ -1,155 +1,348 @@
-use regex::Regex;
-use std::{collections::HashMap, hash::Hash};
+use smallvec::SmallVec;
+use std::{fmt, fs, hash::Hash, rc::Rc};
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub enum Cube {
-    Red,
-    Green,
-    Blue,
-}
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Cube(Rc<str>);
 
 impl TryFrom<&str> for Cube {
-    type Error = &'static str;
+    type Error = String;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "red" => Ok(Cube::Red),
-            "green" => Ok(Cube::Green),
-            "blue" => Ok(Cube::Blue),
-            _ => Err(stringify!("Cannot convert Cube from {}", value)),
+        if !value.is_empty() && value.chars().all(|character| character.is_alphabetic()) {
+            Ok(Cube(Rc::from(value)))
+        } else {
+            Err(format!("Cannot convert Cube from \"{}\"", value))
         }
     }
 }
 
-pub type Subset = HashMap<Cube, u32>;
+impl fmt::Display for Cube {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
 
-pub struct Game {
-    id: u32,
-    subsets: Vec<Subset>,
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum DuplicateCubes {
+    #[default]
+    Sum,
+    Replace,
+    Reject,
 }
 
-impl Game {
-    pub fn try_build(input: &str) -> Option<Self> {
-        let id = Self::try_parse_id(input);
-        let subsets = Self::try_parse_subsets(input);
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AbsentCubes {
+    Skip,
+    CountAsZero,
+}
 
-        if let (Some(id), Some(subsets)) = (id, subsets) {
-            Some(Game { id, subsets })
-        } else {
-            None
-        }
+// a draw rarely contains more than a handful of colours, so keep them inline
+#[derive(Clone, Default, Debug)]
+pub struct Subset {
+    cubes: SmallVec<[(Cube, u32); 3]>,
+}
+
+impl Subset {
+    pub fn new() -> Self {
+        Subset::default()
     }
 
-    fn try_parse_id(input: &str) -> Option<u32> {
-        let game_regex = Regex::new(r"Game (\d+)+:");
+    pub fn insert(&mut self, cube: Cube, amount: u32) -> Option<u32> {
+        match self
+            .cubes
+            .iter_mut()
+            .find(|(other_cube, _)| *other_cube == cube)
+        {
+            Some((_, other_amount)) => Some(std::mem::replace(other_amount, amount)),
+            None => {
+                self.cubes.push((cube, amount));
+                None
+            }
+        }
+    }
 
-        match game_regex {
-            Ok(game_regex) => match game_regex.captures(input) {
-                Some(captures) => {
-                    let game_id = &captures[1];
-                    if let Ok(game_id) = game_id.parse::<u32>() {
-                        Some(game_id)
-                    } else {
-                        println!("Cannot convert game id to u32 ({})", game_id);
-                        None
-                    }
+    pub fn try_add(
+        &mut self,
+        cube: Cube,
+        amount: u32,
+        duplicate_cubes: DuplicateCubes,
+    ) -> Result<(), String> {
+        match self
+            .cubes
+            .iter_mut()
+            .find(|(other_cube, _)| *other_cube == cube)
+        {
+            Some((_, other_amount)) => match duplicate_cubes {
+                DuplicateCubes::Sum => {
+                    *other_amount = other_amount.checked_add(amount).ok_or_else(|| {
+                        format!("Amount of {} cubes overflows when summing duplicates", cube)
+                    })?
                 }
-                None => {
-                    println!("No game id found in \"{}\"", input);
-                    None
+                DuplicateCubes::Replace => *other_amount = amount,
+                DuplicateCubes::Reject => {
+                    return Err(format!("Cube {} is given more than once", cube))
                 }
             },
-            Err(game_regex_error) => {
-                println!("Cannot get valid game regex, {}", game_regex_error);
-                None
-            }
+            None => self.cubes.push((cube, amount)),
         }
+
+        Ok(())
     }
 
-    fn try_parse_subsets(input: &str) -> Option<Vec<Subset>> {
-        let splitted_input: Vec<&str> = input.split(':').collect();
-        if splitted_input.len() != 2 {
-            println!("Cannot find subset input");
-            return None;
-        }
+    pub fn get(&self, cube: &Cube) -> Option<u32> {
+        self.cubes
+            .iter()
+            .find(|(other_cube, _)| other_cube == cube)
+            .map(|(_, amount)| *amount)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Cube, u32)> {
+        self.cubes.iter().map(|(cube, amount)| (cube, *amount))
+    }
 
-        let subsets_input = splitted_input[1];
+    pub fn get_power(&self, cubes: &[Cube], absent_cubes: AbsentCubes) -> Option<u64> {
+        let mut power: u64 = 1;
 
-        let subset_entry_regex = match Regex::new(&format!(r"(\d+)+ {}", Self::get_color_regex())) {
-            Ok(subset_entry_regex) => subset_entry_regex,
-            Err(subset_entry_regex_error) => {
-                println!(
-                    "Cannot get valid subset entry regex, {}",
-                    subset_entry_regex_error
-                );
-                return None;
+        for cube in cubes {
+            match (self.get(cube), absent_cubes) {
+                (Some(amount), _) => power = power.checked_mul(amount as u64)?,
+                (None, AbsentCubes::Skip) => (),
+                (None, AbsentCubes::CountAsZero) => return Some(0),
             }
-        };
+        }
 
-        let mut subsets = Vec::new();
+        Some(power)
+    }
 
+    pub fn is_empty(&self) -> bool {
+        self.cubes.is_empty()
+    }
+}
+
+#[derive(Default)]
+pub struct GameParser {
+    cubes: Vec<Cube>,
+    duplicate_cubes: DuplicateCubes,
+}
+
+impl GameParser {
+    pub fn new() -> Self {
+        GameParser::default()
+    }
+
+    pub fn with_duplicate_cubes(duplicate_cubes: DuplicateCubes) -> Self {
+        GameParser {
+            cubes: Vec::new(),
+            duplicate_cubes,
+        }
+    }
+
+    pub fn try_parse(&mut self, input: &str) -> Result<Game, String> {
+        let (game_input, subsets_input) = input
+            .split_once(':')
+            .ok_or_else(|| format!("Cannot find subset input in \"{}\"", input))?;
+
+        let id = Self::try_parse_id(game_input)?;
+
+        let mut subsets = SmallVec::new();
         for subset_input in subsets_input.split(';') {
-            let mut subset = Subset::new();
-
-            for subset_entry_input in subset_input.split(',') {
-                match subset_entry_regex.captures(subset_entry_input) {
-                    Some(captures) => {
-                        let amount = &captures[1].parse::<u32>();
-                        let cube = Cube::try_from(&captures[2]);
-
-                        if let (Ok(amount), Ok(cube)) = (amount, cube) {
-                            subset.insert(cube, *amount);
-                        } else {
-                            println!("Subset entry is invalid, \"{}\"", subset_entry_input);
-                        }
-                    }
-                    None => {
-                        println!("No subset entry found in \"{}\"", subset_entry_input);
-                    }
+            subsets.push(self.try_parse_subset(subset_input)?);
+        }
+
+        Ok(Game { id, subsets })
+    }
+
+    fn try_parse_id(input: &str) -> Result<u32, String> {
+        match input.trim().strip_prefix("Game ") {
+            Some(game_id) => game_id
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| format!("Cannot convert game id to u32 ({})", game_id)),
+            None => Err(format!("No game id found in \"{}\"", input)),
+        }
+    }
+
+    fn try_parse_subset(&mut self, input: &str) -> Result<Subset, String> {
+        let mut subset = Subset::new();
+
+        for subset_entry_input in input.split(',') {
+            let (amount, cube) = match subset_entry_input.trim().split_once(' ') {
+                Some(subset_entry) => subset_entry,
+                None => {
+                    return Err(format!(
+                        "No subset entry found in \"{}\"",
+                        subset_entry_input
+                    ))
                 }
+            };
+
+            let amount = amount.parse::<u32>().map_err(|amount_error| {
+                format!(
+                    "Subset entry amount is invalid in \"{}\", {}",
+                    subset_entry_input, amount_error
+                )
+            })?;
+            let cube = self.get_cube(cube.trim())?;
+
+            subset.try_add(cube, amount, self.duplicate_cubes)?;
+        }
+
+        Ok(subset)
+    }
+
+    fn get_cube(&mut self, input: &str) -> Result<Cube, String> {
+        // colours are interned so that parsing known colours does not allocate
+        if let Some(cube) = self.cubes.iter().find(|cube| &*cube.0 == input) {
+            return Ok(cube.clone());
+        }
+
+        let cube = Cube::try_from(input)?;
+        self.cubes.push(cube.clone());
+
+        Ok(cube)
+    }
+}
+
+pub struct Bag {
+    cubes: Subset,
+}
+
+impl Bag {
+    pub fn try_build(input: &str) -> Result<Self, String> {
+        let mut game_parser = GameParser::with_duplicate_cubes(DuplicateCubes::Reject);
+
+        let mut cubes = Subset::new();
+
+        for bag_input in input.lines().map(|line| line.trim()) {
+            if bag_input.is_empty() {
+                continue;
             }
 
-            subsets.push(subset);
+            for (cube, amount) in game_parser.try_parse_subset(bag_input)?.iter() {
+                cubes.try_add(cube.clone(), amount, DuplicateCubes::Reject)?;
+            }
         }
 
-        if subsets.is_empty() {
-            None
+        if cubes.is_empty() {
+            Err(String::from("Bag does not contain any cubes"))
         } else {
-            Some(subsets)
+            Ok(Bag { cubes })
         }
     }
 
-    fn get_color_regex() -> &'static str {
-        "(red|green|blue)"
+    pub fn try_build_from_file(file_name: &str) -> Result<Self, String> {
+        let file_content = fs::read_to_string(file_name)
+            .map_err(|file_error| format!("Cannot read bag file {}, {}", file_name, file_error))?;
+
+        Self::try_build(&file_content)
+    }
+
+    pub fn get_limit(&self, cube: &Cube) -> Option<u32> {
+        self.cubes.get(cube)
+    }
+
+    pub fn get_cubes(&self) -> Vec<Cube> {
+        self.cubes.iter().map(|(cube, _)| cube.clone()).collect()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Violation {
+    subset_index: usize,
+    cube: Cube,
+    amount: u32,
+    limit: u32,
+}
+
+impl Violation {
+    pub fn get_subset_index(&self) -> usize {
+        self.subset_index
+    }
+
+    pub fn get_cube(&self) -> &Cube {
+        &self.cube
+    }
+
+    pub fn get_amount(&self) -> u32 {
+        self.amount
+    }
+
+    pub fn get_limit(&self) -> u32 {
+        self.limit
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "draw {} has {} {} cubes, but the bag only holds {}",
+            self.subset_index + 1,
+            self.amount,
+            self.cube,
+            self.limit
+        )
+    }
+}
+
+pub struct Game {
+    id: u32,
+    subsets: SmallVec<[Subset; 6]>,
+}
+
+impl Game {
+    pub fn try_build(input: &str) -> Result<Self, String> {
+        GameParser::new().try_parse(input)
     }
 
     pub fn get_id(&self) -> u32 {
         self.id
     }
 
-    pub fn is_possible(&self, subset_limit: &Subset) -> bool {
-        for subset in &self.subsets {
-            for (cube, limit_amount) in subset_limit {
-                if let Some(amount) = subset.get(cube) {
-                    if amount > limit_amount {
-                        return false;
+    pub fn get_subsets(&self) -> &[Subset] {
+        &self.subsets
+    }
+
+    pub fn is_possible(&self, bag: &Bag) -> Result<bool, String> {
+        Ok(self.get_violations(bag)?.is_empty())
+    }
+
+    pub fn get_violations(&self, bag: &Bag) -> Result<Vec<Violation>, String> {
+        let mut violations = Vec::new();
+
+        for (subset_index, subset) in self.subsets.iter().enumerate() {
+            for (cube, amount) in subset.iter() {
+                match bag.get_limit(cube) {
+                    Some(limit) => {
+                        if amount > limit {
+                            violations.push(Violation {
+                                subset_index,
+                                cube: cube.clone(),
+                                amount,
+                                limit,
+                            });
+                        }
+                    }
+                    None => {
+                        return Err(format!(
+                            "Game {} draws cube of unknown colour {}",
+                            self.id, cube
+                        ))
                     }
                 }
             }
         }
 
-        true
+        Ok(violations)
     }
 
     pub fn get_minimum_number_of_cubes(&self) -> Subset {
-        let mut subset_limit = Subset::from([(Cube::Red, 0), (Cube::Green, 0), (Cube::Blue, 0)]);
+        let mut subset_limit = Subset::new();
 
         for subset in &self.subsets {
-            for (cube, amount) in subset {
-                if let Some(limit) = subset_limit.get(&cube) {
-                    if *amount > *limit {
-                        subset_limit.insert(*cube, *amount);
-                    }
+            for (cube, amount) in subset.iter() {
+                if amount > subset_limit.get(cube).unwrap_or(0) {
+                    subset_limit.insert(cube.clone(), amount);
                 }
             }
         }
@@ -157,3 +350,20 @@ impl Game {
         subset_limit
     }
 }
+
+// every colour drawn in any of the games, sorted
+pub fn get_cubes(games: &[Game]) -> Vec<Cube> {
+    let mut cubes: Vec<Cube> = Vec::new();
+
+    for game in games {
+        for (cube, _) in game.get_minimum_number_of_cubes().iter() {
+            if !cubes.contains(cube) {
+                cubes.push(cube.clone());
+            }
+        }
+    }
+
+    cubes.sort();
+
+    cubes
+}
@@ -0,0 +1,440 @@
+use super::game::{self, Cube, Game, Subset};
+
+// chi-square quantile with one degree of freedom at 95%, used for profile likelihood intervals
+const CONFIDENCE_INTERVAL_THRESHOLD: f64 = 3.841;
+// bisection steps when splitting a total number of cubes between the colours
+const NUMBER_OF_BISECTION_STEPS: usize = 64;
+
+#[derive(Debug, PartialEq)]
+pub struct CubeEstimate {
+    cube: Cube,
+    minimum: u32,
+    most_likely: u32,
+    lower_bound: u32,
+    upper_bound: u32,
+    is_capped: bool,
+    is_unbounded: bool,
+}
+
+impl CubeEstimate {
+    pub fn get_cube(&self) -> &Cube {
+        &self.cube
+    }
+
+    pub fn get_minimum(&self) -> u32 {
+        self.minimum
+    }
+
+    pub fn get_most_likely(&self) -> u32 {
+        self.most_likely
+    }
+
+    // the most likely bag reaches the cap in at least one colour, which shifts every estimate
+    pub fn is_capped(&self) -> bool {
+        self.is_capped
+    }
+
+    // the likelihood still grows when the whole bag grows at the cap, so there is no finite
+    // most likely bag and the estimates only reflect the cap
+    pub fn is_unbounded(&self) -> bool {
+        self.is_unbounded
+    }
+
+    pub fn get_lower_bound(&self) -> u32 {
+        self.lower_bound
+    }
+
+    pub fn get_upper_bound(&self) -> u32 {
+        self.upper_bound
+    }
+}
+
+pub struct BagInference<'a> {
+    cubes: Vec<Cube>,
+    // per colour, how many draws had each non-zero amount of that colour
+    drawn_amounts: Vec<Vec<(u32, u32)>>,
+    // how many draws had each number of cubes
+    drawn_totals: Vec<(u32, u32)>,
+    games: &'a [Game],
+}
+
+impl<'a> BagInference<'a> {
+    pub fn build(games: &'a [Game]) -> Self {
+        let cubes = game::get_cubes(games);
+
+        let mut drawn_amounts = vec![Vec::new(); cubes.len()];
+        let mut drawn_totals = Vec::new();
+
+        for game in games {
+            for subset in game.get_subsets() {
+                let mut drawn_total = 0;
+
+                for (cube_index, cube) in cubes.iter().enumerate() {
+                    let drawn_amount = subset.get(cube).unwrap_or(0);
+                    drawn_total += drawn_amount;
+
+                    if drawn_amount > 0 {
+                        add_occurrence(&mut drawn_amounts[cube_index], drawn_amount);
+                    }
+                }
+
+                add_occurrence(&mut drawn_totals, drawn_total);
+            }
+        }
+
+        BagInference {
+            cubes,
+            drawn_amounts,
+            drawn_totals,
+            games,
+        }
+    }
+
+    pub fn get_minimal_bag(&self) -> Subset {
+        let mut minimal_bag = Subset::new();
+
+        for cube in &self.cubes {
+            minimal_bag.insert(cube.clone(), 0);
+        }
+
+        for game in self.games {
+            for (cube, amount) in game.get_minimum_number_of_cubes().iter() {
+                if amount > minimal_bag.get(cube).unwrap_or(0) {
+                    minimal_bag.insert(cube.clone(), amount);
+                }
+            }
+        }
+
+        minimal_bag
+    }
+
+    pub fn get_estimates(&self, max_number_of_cubes: u32) -> Vec<CubeEstimate> {
+        let minimal_bag = self.get_minimal_bag();
+        let minimum: Vec<u32> = self
+            .cubes
+            .iter()
+            .map(|cube| minimal_bag.get(cube).unwrap_or(0))
+            .collect();
+        let maximum: Vec<u32> = minimum
+            .iter()
+            .map(|amount| max_number_of_cubes.max(*amount))
+            .collect();
+
+        let (most_likely, most_likely_log_likelihood) =
+            self.get_most_likely_composition(&minimum, &maximum);
+
+        let is_capped = most_likely
+            .iter()
+            .zip(&maximum)
+            .any(|(amount, maximum)| amount == maximum);
+        let is_unbounded = self.is_unbounded(most_likely_log_likelihood);
+
+        let mut estimates = Vec::new();
+
+        for (cube_index, cube) in self.cubes.iter().enumerate() {
+            let (lower_bound, upper_bound) = self.get_profile_likelihood_interval(
+                &most_likely,
+                most_likely_log_likelihood,
+                cube_index,
+                (&minimum, &maximum),
+            );
+
+            estimates.push(CubeEstimate {
+                cube: cube.clone(),
+                minimum: minimum[cube_index],
+                most_likely: most_likely[cube_index],
+                lower_bound,
+                upper_bound,
+                is_capped,
+                is_unbounded,
+            });
+        }
+
+        estimates
+    }
+
+    // the likelihood of a bag with a fixed total splits into one concave part per colour, so the
+    // best bag for a total follows from the marginal gains of the colours, the totals are then
+    // searched assuming the likelihood has a single peak over the total
+    fn get_most_likely_composition(&self, minimum: &[u32], maximum: &[u32]) -> (Vec<u32>, f64) {
+        let get_best_composition = |total: u64| {
+            let composition = self.get_best_composition_of_total(total, minimum, maximum);
+            let log_likelihood = self.get_log_likelihood(&composition);
+
+            (composition, log_likelihood)
+        };
+
+        let minimum_total: u64 = minimum.iter().map(|amount| *amount as u64).sum();
+        let maximum_total: u64 = maximum.iter().map(|amount| *amount as u64).sum();
+
+        // the best bags of neighbouring totals alternate between even and uneven splits between
+        // the colours, so the totals are compared in windows of one total per colour
+        let window = minimum.len().max(1) as u64;
+        let get_best_composition_of_window = |first_total: u64| {
+            (first_total..=(first_total + window - 1).min(maximum_total))
+                .map(get_best_composition)
+                .reduce(|best, candidate| {
+                    if candidate.1 > best.1 {
+                        candidate
+                    } else {
+                        best
+                    }
+                })
+                .unwrap()
+        };
+
+        let (mut lowest_total, mut highest_total) = (minimum_total, maximum_total);
+
+        while highest_total - lowest_total > 2 {
+            let third = (highest_total - lowest_total) / 3;
+            let (lower_total, higher_total) = (lowest_total + third, highest_total - third);
+
+            if get_best_composition_of_window(lower_total).1
+                < get_best_composition_of_window(higher_total).1
+            {
+                lowest_total = lower_total + 1;
+            } else {
+                highest_total = higher_total;
+            }
+        }
+
+        (lowest_total..=highest_total)
+            .map(get_best_composition_of_window)
+            .reduce(|best, candidate| {
+                if candidate.1 > best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            })
+            .unwrap()
+    }
+
+    // as the bag grows with fixed proportions the draws become multinomial samples, so no bag
+    // within the cap is most likely when the best of them stays below the multinomial likelihood
+    // at the observed proportions
+    fn is_unbounded(&self, most_likely_log_likelihood: f64) -> bool {
+        most_likely_log_likelihood < self.get_limit_log_likelihood()
+    }
+
+    fn get_limit_log_likelihood(&self) -> f64 {
+        let mut limit_log_likelihood = 0.0;
+
+        for (drawn_total, number_of_draws) in &self.drawn_totals {
+            limit_log_likelihood += *number_of_draws as f64 * get_log_factorial(*drawn_total);
+        }
+
+        let drawn_cubes: Vec<f64> = self
+            .drawn_amounts
+            .iter()
+            .map(|drawn_amounts| {
+                drawn_amounts
+                    .iter()
+                    .map(|(drawn_amount, number_of_draws)| {
+                        limit_log_likelihood -=
+                            *number_of_draws as f64 * get_log_factorial(*drawn_amount);
+
+                        *drawn_amount as f64 * *number_of_draws as f64
+                    })
+                    .sum()
+            })
+            .collect();
+        let number_of_drawn_cubes: f64 = drawn_cubes.iter().sum();
+
+        for drawn_cubes in drawn_cubes {
+            if drawn_cubes > 0.0 {
+                limit_log_likelihood += drawn_cubes * (drawn_cubes / number_of_drawn_cubes).ln();
+            }
+        }
+
+        limit_log_likelihood
+    }
+
+    // every colour gets the cubes whose marginal gain exceeds a common threshold, the threshold
+    // is bisected until the cubes add up to the total, cubes with a gain equal to the threshold
+    // are handed out last
+    fn get_best_composition_of_total(
+        &self,
+        total: u64,
+        minimum: &[u32],
+        maximum: &[u32],
+    ) -> Vec<u32> {
+        let get_composition = |threshold: f64| -> Vec<u32> {
+            (0..minimum.len())
+                .map(|cube_index| {
+                    let (mut lowest, mut highest) = (minimum[cube_index], maximum[cube_index]);
+
+                    while lowest < highest {
+                        let middle = lowest + (highest - lowest) / 2;
+
+                        if self.get_marginal_log_likelihood(cube_index, middle) > threshold {
+                            lowest = middle + 1;
+                        } else {
+                            highest = middle;
+                        }
+                    }
+
+                    lowest
+                })
+                .collect()
+        };
+        let get_total =
+            |composition: &[u32]| -> u64 { composition.iter().map(|amount| *amount as u64).sum() };
+
+        // the marginal gains are never negative and never exceed the gain of the first cube
+        let mut low_threshold = -1.0;
+        let mut high_threshold = (0..minimum.len())
+            .filter(|cube_index| minimum[*cube_index] < maximum[*cube_index])
+            .map(|cube_index| self.get_marginal_log_likelihood(cube_index, minimum[cube_index]))
+            .fold(0.0, f64::max);
+
+        for _ in 0..NUMBER_OF_BISECTION_STEPS {
+            let threshold = (low_threshold + high_threshold) / 2.0;
+
+            if get_total(&get_composition(threshold)) >= total {
+                low_threshold = threshold;
+            } else {
+                high_threshold = threshold;
+            }
+        }
+
+        let mut composition = get_composition(high_threshold);
+        let mut remaining_cubes = total.saturating_sub(get_total(&composition));
+
+        while remaining_cubes > 0 {
+            let best_cube_index = (0..composition.len())
+                .filter(|cube_index| composition[*cube_index] < maximum[*cube_index])
+                .max_by(|first_cube_index, second_cube_index| {
+                    self.get_marginal_log_likelihood(
+                        *first_cube_index,
+                        composition[*first_cube_index],
+                    )
+                    .total_cmp(&self.get_marginal_log_likelihood(
+                        *second_cube_index,
+                        composition[*second_cube_index],
+                    ))
+                });
+
+            match best_cube_index {
+                Some(cube_index) => composition[cube_index] += 1,
+                None => break,
+            }
+
+            remaining_cubes -= 1;
+        }
+
+        composition
+    }
+
+    // the profile likelihood of an amount maximises the likelihood over the other colours, it is
+    // assumed to fall off on both sides of the most likely amount
+    fn get_profile_likelihood_interval(
+        &self,
+        most_likely: &[u32],
+        most_likely_log_likelihood: f64,
+        cube_index: usize,
+        (minimum, maximum): (&[u32], &[u32]),
+    ) -> (u32, u32) {
+        let is_inside_interval = |amount: u32| {
+            let mut fixed_minimum = minimum.to_vec();
+            let mut fixed_maximum = maximum.to_vec();
+            fixed_minimum[cube_index] = amount;
+            fixed_maximum[cube_index] = amount;
+
+            let (_, profile_log_likelihood) =
+                self.get_most_likely_composition(&fixed_minimum, &fixed_maximum);
+
+            2.0 * (most_likely_log_likelihood - profile_log_likelihood)
+                <= CONFIDENCE_INTERVAL_THRESHOLD
+        };
+
+        let (mut lowest, mut highest) = (minimum[cube_index], most_likely[cube_index]);
+        while lowest < highest {
+            let middle = lowest + (highest - lowest) / 2;
+
+            if is_inside_interval(middle) {
+                highest = middle;
+            } else {
+                lowest = middle + 1;
+            }
+        }
+        let lower_bound = lowest;
+
+        let (mut lowest, mut highest) = (most_likely[cube_index], maximum[cube_index]);
+        while lowest < highest {
+            let middle = lowest + (highest - lowest).div_ceil(2);
+
+            if is_inside_interval(middle) {
+                lowest = middle;
+            } else {
+                highest = middle - 1;
+            }
+        }
+        let upper_bound = lowest;
+
+        (lower_bound, upper_bound)
+    }
+
+    // each draw is a multivariate hypergeometric sample, the cubes are put back between draws
+    fn get_log_likelihood(&self, composition: &[u32]) -> f64 {
+        let number_of_cubes: u64 = composition.iter().map(|amount| *amount as u64).sum();
+
+        let mut log_likelihood = 0.0;
+
+        for (cube_index, amount) in composition.iter().enumerate() {
+            for (drawn_amount, number_of_draws) in &self.drawn_amounts[cube_index] {
+                log_likelihood += *number_of_draws as f64
+                    * get_log_binomial_coefficient(*amount as u64, *drawn_amount);
+            }
+        }
+
+        for (drawn_total, number_of_draws) in &self.drawn_totals {
+            log_likelihood -= *number_of_draws as f64
+                * get_log_binomial_coefficient(number_of_cubes, *drawn_total);
+        }
+
+        log_likelihood
+    }
+
+    // the gain of the colour's part of the likelihood from one more cube, shrinks as the amount
+    // grows, the amount is at least the largest drawn amount
+    fn get_marginal_log_likelihood(&self, cube_index: usize, amount: u32) -> f64 {
+        let amount = amount as f64;
+
+        self.drawn_amounts[cube_index]
+            .iter()
+            .map(|(drawn_amount, number_of_draws)| {
+                *number_of_draws as f64
+                    * ((amount + 1.0).ln() - (amount + 1.0 - *drawn_amount as f64).ln())
+            })
+            .sum()
+    }
+}
+
+fn add_occurrence(occurrences: &mut Vec<(u32, u32)>, value: u32) {
+    match occurrences
+        .iter_mut()
+        .find(|(other_value, _)| *other_value == value)
+    {
+        Some((_, number_of_occurrences)) => *number_of_occurrences += 1,
+        None => occurrences.push((value, 1)),
+    }
+}
+
+fn get_log_factorial(n: u32) -> f64 {
+    (2..=n).map(|i| (i as f64).ln()).sum()
+}
+
+fn get_log_binomial_coefficient(n: u64, k: u32) -> f64 {
+    if k as u64 > n {
+        return f64::NEG_INFINITY;
+    }
+
+    let mut log_binomial_coefficient = 0.0;
+
+    for i in 0..k as u64 {
+        log_binomial_coefficient += ((n - i) as f64).ln() - ((i + 1) as f64).ln();
+    }
+
+    log_binomial_coefficient
+}
@@ -1,68 +1,137 @@
 use std::fs;
 mod game;
-use game::{Cube, Game, Subset};
+mod inference;
+pub use game::{get_cubes, AbsentCubes, Bag, Cube, DuplicateCubes, Game, GameParser, Subset};
+pub use inference::{BagInference, CubeEstimate};
+
+pub const DEFAULT_BAG: &str = "12 red, 13 green, 14 blue";
 
 pub fn part_1(file_name: &str) -> u32 {
-    let subset_limit = Subset::from([(Cube::Red, 12), (Cube::Green, 13), (Cube::Blue, 14)]);
+    match Bag::try_build(DEFAULT_BAG) {
+        Ok(bag) => part_1_with_bag(file_name, &bag),
+        Err(bag_error) => {
+            println!("Failed to get default bag, {}", bag_error);
+            0
+        }
+    }
+}
+
+pub fn part_1_with_bag(file_name: &str, bag: &Bag) -> u32 {
+    let games = try_get_games(file_name);
+
+    match games.and_then(|games| get_sum_of_possible_game_ids(games, bag)) {
+        Ok(sum_of_possible_game_ids) => sum_of_possible_game_ids,
+        Err(games_error) => {
+            println!("Failed to get sum of possible game ids, {}", games_error);
+            0
+        }
+    }
+}
+
+pub fn get_impossible_games_report(file_name: &str, bag: &Bag) -> Result<Vec<String>, String> {
+    let games = try_get_games(file_name)?;
+
+    let mut report = Vec::new();
+
+    for game in games {
+        let violations = game.get_violations(bag)?;
+
+        if !violations.is_empty() {
+            report.push(format!("Game {} is impossible:", game.get_id()));
+
+            for violation in violations {
+                report.push(format!("  {}", violation));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+pub fn get_bag_estimates(
+    file_name: &str,
+    max_number_of_cubes: u32,
+) -> Result<Vec<CubeEstimate>, String> {
+    let games = try_get_games(file_name)?;
 
-    let games = get_games(file_name);
+    Ok(BagInference::build(&games).get_estimates(max_number_of_cubes))
+}
+
+// the power is taken over every colour drawn in any game, a game without one of them has no power
+pub fn part_2(file_name: &str) -> u64 {
+    let games = try_get_games(file_name);
 
-    get_sum_of_possible_game_ids(games, subset_limit)
+    match games.and_then(|games| {
+        let cubes = get_cubes(&games);
+        get_sum_of_power(games, &cubes, AbsentCubes::CountAsZero)
+    }) {
+        Ok(sum_of_power) => sum_of_power,
+        Err(games_error) => {
+            println!("Failed to get sum of power, {}", games_error);
+            0
+        }
+    }
 }
 
-pub fn part_2(file_name: &str) -> u32 {
-    let games = get_games(file_name);
+pub fn part_2_with_cubes(file_name: &str, cubes: &[Cube], absent_cubes: AbsentCubes) -> u64 {
+    let games = try_get_games(file_name);
 
-    get_sum_of_power(games)
+    match games.and_then(|games| get_sum_of_power(games, cubes, absent_cubes)) {
+        Ok(sum_of_power) => sum_of_power,
+        Err(games_error) => {
+            println!("Failed to get sum of power, {}", games_error);
+            0
+        }
+    }
 }
 
-fn get_games(file_name: &str) -> Vec<Game> {
+fn try_get_games(file_name: &str) -> Result<Vec<Game>, String> {
     let file_content =
         fs::read_to_string(file_name).expect("input file should be located in input folder");
-    let file_lines: Vec<&str> = file_content.split('\n').collect();
+    let file_lines: Vec<&str> = file_content
+        .split('\n')
+        .map(|file_line| file_line.trim())
+        .filter(|file_line| !file_line.is_empty())
+        .collect();
+
+    let mut game_parser = GameParser::new();
 
     let mut games = Vec::new();
     for line in file_lines {
-        if let Some(game) = Game::try_build(line) {
-            games.push(game);
-        }
+        games.push(game_parser.try_parse(line)?);
     }
 
-    games
+    Ok(games)
 }
 
-fn get_sum_of_possible_game_ids(games: Vec<Game>, subset_limit: Subset) -> u32 {
+fn get_sum_of_possible_game_ids(games: Vec<Game>, bag: &Bag) -> Result<u32, String> {
     let mut sum_of_possible_game_ids = 0;
 
     for game in games {
-        if game.is_possible(&subset_limit) {
+        if game.is_possible(bag)? {
             sum_of_possible_game_ids += game.get_id();
         }
     }
 
-    sum_of_possible_game_ids
+    Ok(sum_of_possible_game_ids)
 }
 
-fn get_sum_of_power(games: Vec<Game>) -> u32 {
-    let mut power = 0;
+fn get_sum_of_power(
+    games: Vec<Game>,
+    cubes: &[Cube],
+    absent_cubes: AbsentCubes,
+) -> Result<u64, String> {
+    let mut power: u64 = 0;
 
     for game in games {
-        power += get_power(game);
-    }
-
-    power
-}
-
-fn get_power(game: Game) -> u32 {
-    let minimum_number_of_cubes = game.get_minimum_number_of_cubes();
-
-    let mut power = 1;
-
-    for amount in minimum_number_of_cubes.values() {
-        power *= amount;
+        power = game
+            .get_minimum_number_of_cubes()
+            .get_power(cubes, absent_cubes)
+            .and_then(|game_power| power.checked_add(game_power))
+            .ok_or_else(|| format!("Power of game {} overflows", game.get_id()))?;
     }
 
-    power
+    Ok(power)
 }
 
 #[cfg(test)]
@@ -80,4 +149,183 @@ mod tests {
         let result = part_2("../input/test_input.txt");
         assert_eq!(result, 2286);
     }
+
+    #[test]
+    fn test_input_part_1_with_bag() {
+        let bag = Bag::try_build("20 red\n13 green, 14 blue").unwrap();
+        let result = part_1_with_bag("../input/test_input.txt", &bag);
+        assert_eq!(result, 11);
+    }
+
+    #[test]
+    fn test_input_impossible_games_report() {
+        let bag = Bag::try_build(DEFAULT_BAG).unwrap();
+        let report = get_impossible_games_report("../input/test_input.txt", &bag).unwrap();
+        assert_eq!(
+            report,
+            vec![
+                "Game 3 is impossible:",
+                "  draw 1 has 20 red cubes, but the bag only holds 12",
+                "Game 4 is impossible:",
+                "  draw 3 has 15 blue cubes, but the bag only holds 14",
+                "  draw 3 has 14 red cubes, but the bag only holds 12",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_input_bag_estimates() {
+        let estimates = get_bag_estimates("../input/test_input.txt", 100).unwrap();
+
+        let minimums: Vec<(String, u32)> = estimates
+            .iter()
+            .map(|estimate| (estimate.get_cube().to_string(), estimate.get_minimum()))
+            .collect();
+        assert_eq!(
+            minimums,
+            vec![
+                (String::from("blue"), 15),
+                (String::from("green"), 13),
+                (String::from("red"), 20)
+            ]
+        );
+
+        // the draws vary more than draws from any finite bag would, so larger bags keep getting
+        // more likely and every estimate only reflects the cap
+        for estimate in estimates {
+            assert!(estimate.is_unbounded());
+            assert!(estimate.is_capped());
+            assert!(estimate.get_minimum() <= estimate.get_lower_bound());
+            assert!(estimate.get_lower_bound() <= estimate.get_most_likely());
+            assert!(estimate.get_most_likely() <= estimate.get_upper_bound());
+        }
+    }
+
+    #[test]
+    fn test_bag_estimates_of_complete_draws() {
+        let games = vec![
+            Game::try_build("Game 1: 3 red, 2 blue; 2 blue, 3 red").unwrap(),
+            Game::try_build("Game 2: 3 red, 2 blue").unwrap(),
+        ];
+
+        let estimates = BagInference::build(&games).get_estimates(50);
+
+        for estimate in estimates {
+            assert!(!estimate.is_capped());
+            assert_eq!(estimate.get_most_likely(), estimate.get_minimum());
+            assert_eq!(estimate.get_lower_bound(), estimate.get_minimum());
+        }
+    }
+
+    #[test]
+    fn test_bag_estimates_of_mixed_draws() {
+        // mixed pairs are more common than in draws with replacement, which favours small bags
+        let games = vec![
+            Game::try_build("Game 1: 1 red, 1 blue; 1 red, 1 blue; 1 red, 1 blue; 2 red; 2 blue")
+                .unwrap(),
+            Game::try_build("Game 2: 1 red, 1 blue; 1 red, 1 blue; 1 red, 1 blue; 2 red; 2 blue")
+                .unwrap(),
+        ];
+        let inference = BagInference::build(&games);
+
+        for estimate in inference.get_estimates(50) {
+            assert!(!estimate.is_unbounded());
+            assert!(!estimate.is_capped());
+            assert_eq!(estimate.get_minimum(), 2);
+            assert_eq!(estimate.get_most_likely(), 3);
+            assert_eq!(estimate.get_lower_bound(), 2);
+        }
+
+        // a cap below the most likely bag holds back every colour
+        for estimate in inference.get_estimates(2) {
+            assert!(!estimate.is_unbounded());
+            assert!(estimate.is_capped());
+            assert_eq!(estimate.get_most_likely(), 2);
+        }
+    }
+
+    #[test]
+    fn test_duplicate_cubes() {
+        let input = "Game 1: 3 red, 4 red, 1 blue";
+
+        let game = GameParser::with_duplicate_cubes(DuplicateCubes::Sum)
+            .try_parse(input)
+            .unwrap();
+        assert_eq!(
+            game.get_subsets()[0].get(&Cube::try_from("red").unwrap()),
+            Some(7)
+        );
+
+        let game = GameParser::with_duplicate_cubes(DuplicateCubes::Replace)
+            .try_parse(input)
+            .unwrap();
+        assert_eq!(
+            game.get_subsets()[0].get(&Cube::try_from("red").unwrap()),
+            Some(4)
+        );
+
+        assert!(GameParser::with_duplicate_cubes(DuplicateCubes::Reject)
+            .try_parse(input)
+            .is_err());
+        assert!(Bag::try_build("12 red\n3 red").is_err());
+    }
+
+    #[test]
+    fn test_power_of_absent_cubes() {
+        let bag = Bag::try_build(DEFAULT_BAG).unwrap();
+        let game = Game::try_build("Game 1: 3 red, 4 green; 5 red").unwrap();
+        let minimum_number_of_cubes = game.get_minimum_number_of_cubes();
+
+        assert_eq!(
+            minimum_number_of_cubes.get_power(&bag.get_cubes(), AbsentCubes::CountAsZero),
+            Some(0)
+        );
+        assert_eq!(
+            minimum_number_of_cubes.get_power(&bag.get_cubes(), AbsentCubes::Skip),
+            Some(20)
+        );
+    }
+
+    #[test]
+    fn test_power_of_arbitrary_colours() {
+        let games = vec![Game::try_build("Game 1: 3 red, 2 green, 4 blue, 5 purple").unwrap()];
+        let cubes = get_cubes(&games);
+        assert_eq!(
+            get_sum_of_power(games, &cubes, AbsentCubes::CountAsZero),
+            Ok(120)
+        );
+
+        let games = vec![
+            Game::try_build("Game 1: 3 red, 2 purple").unwrap(),
+            Game::try_build("Game 2: 4 red; 1 teal").unwrap(),
+        ];
+        let cubes = get_cubes(&games);
+        assert_eq!(
+            get_sum_of_power(games, &cubes, AbsentCubes::CountAsZero),
+            Ok(0)
+        );
+
+        let games = vec![
+            Game::try_build("Game 1: 3 red, 2 purple").unwrap(),
+            Game::try_build("Game 2: 4 red; 1 teal").unwrap(),
+        ];
+        assert_eq!(
+            get_sum_of_power(games, &cubes, AbsentCubes::Skip),
+            Ok(6 + 4)
+        );
+    }
+
+    #[test]
+    fn test_unknown_colour() {
+        let bag = Bag::try_build("12 red, 13 green").unwrap();
+        let game = Game::try_build("Game 1: 3 blue, 4 red; 1 red").unwrap();
+        assert!(game.is_possible(&bag).is_err());
+    }
+
+    #[test]
+    fn test_arbitrary_colours() {
+        let bag = Bag::try_build("2 purple, 1 teal").unwrap();
+        let game = Game::try_build("Game 7: 2 purple; 1 teal, 1 purple").unwrap();
+        assert_eq!(game.is_possible(&bag), Ok(true));
+    }
 }
@@ -1,7 +1,116 @@
-use advent_of_code_2023_day_02::{part_1, part_2};
+use advent_of_code_2023_day_02::{
+    get_bag_estimates, get_impossible_games_report, part_1_with_bag, part_2, Bag, DEFAULT_BAG,
+};
+use std::env;
 
 fn main() {
-    println!("Result of part 1: {}", part_1("../input/input.txt"));
+    let args: Vec<String> = env::args().collect();
+
+    let mut bag = None;
+    let mut print_report = false;
+    let mut max_number_of_cubes = None;
+
+    let mut arg_index = 1;
+    while let Some(arg) = args.get(arg_index) {
+        match (arg.as_str(), args.get(arg_index + 1)) {
+            ("--bag", Some(bag_input)) => {
+                bag = Some(Bag::try_build(bag_input));
+                arg_index += 1;
+            }
+            ("--bag-file", Some(bag_file_name)) => {
+                bag = Some(Bag::try_build_from_file(bag_file_name));
+                arg_index += 1;
+            }
+            ("--report", _) => print_report = true,
+            ("--infer", Some(max_number_of_cubes_input)) => {
+                match max_number_of_cubes_input.parse::<u32>() {
+                    Ok(max_number_of_cubes_input) => {
+                        max_number_of_cubes = Some(max_number_of_cubes_input)
+                    }
+                    Err(max_number_of_cubes_error) => {
+                        println!(
+                            "Cannot parse maximum number of cubes, {}",
+                            max_number_of_cubes_error
+                        );
+                        return;
+                    }
+                }
+                arg_index += 1;
+            }
+            _ => {
+                println!(
+                    "Usage: {} [--bag \"<cubes>\" | --bag-file <file>] [--report] [--infer <max cubes>]",
+                    args[0]
+                );
+                return;
+            }
+        }
+
+        arg_index += 1;
+    }
+
+    let bag = match bag {
+        Some(Ok(bag)) => bag,
+        Some(Err(bag_error)) => {
+            println!("Cannot get bag, {}", bag_error);
+            return;
+        }
+        None => match Bag::try_build(DEFAULT_BAG) {
+            Ok(bag) => bag,
+            Err(bag_error) => {
+                println!("Cannot get default bag, {}", bag_error);
+                return;
+            }
+        },
+    };
+
+    println!(
+        "Result of part 1: {}",
+        part_1_with_bag("../input/input.txt", &bag)
+    );
+
+    if print_report {
+        match get_impossible_games_report("../input/input.txt", &bag) {
+            Ok(report) => {
+                for line in report {
+                    println!("{}", line);
+                }
+            }
+            Err(report_error) => println!("Cannot get impossible games report, {}", report_error),
+        }
+    }
+
+    if let Some(max_number_of_cubes) = max_number_of_cubes {
+        match get_bag_estimates("../input/input.txt", max_number_of_cubes) {
+            Ok(estimates) => {
+                for estimate in estimates {
+                    if estimate.is_unbounded() {
+                        println!(
+                            "{}: at least {}, no most likely amount up to the cap, larger bags keep getting more likely",
+                            estimate.get_cube(),
+                            estimate.get_minimum()
+                        );
+                        continue;
+                    }
+
+                    println!(
+                        "{}: at least {}, most likely {}{} (95% interval {}..={})",
+                        estimate.get_cube(),
+                        estimate.get_minimum(),
+                        estimate.get_most_likely(),
+                        if estimate.is_capped() {
+                            " (capped)"
+                        } else {
+                            ""
+                        },
+                        estimate.get_lower_bound(),
+                        estimate.get_upper_bound()
+                    );
+                }
+            }
+            Err(estimates_error) => println!("Cannot get bag estimates, {}", estimates_error),
+        }
+    }
 
     println!("Result of part 2: {}", part_2("../input/input.txt"));
 }
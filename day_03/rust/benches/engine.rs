@@ -0,0 +1,53 @@
+use advent_of_code_2023_day_03::{Engine, RaggedRows};
+use std::{hint::black_box, time::Instant};
+
+const NUMBER_OF_ROWS: usize = 2_000;
+const NUMBER_OF_TILES: usize = 100;
+
+fn main() {
+    let input = get_input();
+    let input_lines: Vec<&str> = input.lines().collect();
+    let number_of_cells = input_lines.len() * input_lines[0].len();
+
+    let start = Instant::now();
+    let engine = match Engine::try_build(input_lines, RaggedRows::Reject) {
+        Ok(engine) => engine,
+        Err(engine_error) => panic!("benchmark input should be valid, {}", engine_error),
+    };
+    let part_number_sum: u64 = black_box(engine.get_valid_part_number_values())
+        .iter()
+        .sum();
+    let gear_ratio_sum: u64 = match engine.get_gear_ratios() {
+        Ok(gear_ratios) => black_box(gear_ratios).iter().sum(),
+        Err(gear_ratio_error) => panic!("benchmark gear ratios should fit, {}", gear_ratio_error),
+    };
+    let elapsed = start.elapsed();
+
+    println!(
+        "Analysed {} cells in {:?} (part numbers {}, gear ratios {})",
+        number_of_cells, elapsed, part_number_sum, gear_ratio_sum
+    );
+}
+
+fn get_input() -> String {
+    let tile = [
+        "467..114..",
+        "...*......",
+        "..35..633.",
+        "......#...",
+        "617*......",
+        ".....+.58.",
+        "..592.....",
+        "......755.",
+        "...$.*....",
+        ".664.598..",
+    ];
+
+    let mut input = String::new();
+    for row_index in 0..NUMBER_OF_ROWS {
+        input.push_str(&tile[row_index % tile.len()].repeat(NUMBER_OF_TILES));
+        input.push('\n');
+    }
+
+    input
+}
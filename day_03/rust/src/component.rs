@@ -0,0 +1,163 @@
+use super::engine::{PartNumber, Symbol};
+use std::collections::BTreeSet;
+
+#[derive(Debug, PartialEq)]
+pub struct Component {
+    part_number_ids: Vec<usize>,
+    symbol_positions: Vec<(usize, usize)>,
+    symbols: BTreeSet<char>,
+    // sum and product are None when they do not fit into u64
+    sum: Option<u64>,
+    product: Option<u64>,
+}
+
+impl Component {
+    pub fn get_part_number_ids(&self) -> &[usize] {
+        &self.part_number_ids
+    }
+
+    pub fn get_symbol_positions(&self) -> &[(usize, usize)] {
+        &self.symbol_positions
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.part_number_ids.len() + self.symbol_positions.len()
+    }
+
+    pub fn get_symbols(&self) -> &BTreeSet<char> {
+        &self.symbols
+    }
+
+    pub fn get_sum(&self) -> Option<u64> {
+        self.sum
+    }
+
+    pub fn get_product(&self) -> Option<u64> {
+        self.product
+    }
+}
+
+// nodes are the part numbers followed by the symbols, edges connect symbols to adjacent part numbers
+pub fn get_components(part_numbers: &[PartNumber], symbols: &[Symbol]) -> Vec<Component> {
+    let mut parents: Vec<usize> = (0..part_numbers.len() + symbols.len()).collect();
+
+    for (symbol_index, symbol) in symbols.iter().enumerate() {
+        for part_number in symbol.get_adjacent_part_numbers() {
+            union(
+                &mut parents,
+                part_numbers.len() + symbol_index,
+                part_number.get_id(),
+            );
+        }
+    }
+
+    let mut root_component_indices = vec![None; parents.len()];
+    let mut components: Vec<Component> = Vec::new();
+
+    for node in 0..parents.len() {
+        let root = find(&mut parents, node);
+
+        let component_index = *root_component_indices[root].get_or_insert_with(|| {
+            components.push(Component {
+                part_number_ids: Vec::new(),
+                symbol_positions: Vec::new(),
+                symbols: BTreeSet::new(),
+                sum: Some(0),
+                product: Some(1),
+            });
+            components.len() - 1
+        });
+        let component = &mut components[component_index];
+
+        if node < part_numbers.len() {
+            let value = part_numbers[node].get_value();
+
+            component.part_number_ids.push(node);
+            component.sum = component.sum.and_then(|sum| sum.checked_add(value));
+            component.product = component
+                .product
+                .and_then(|product| product.checked_mul(value));
+        } else {
+            let symbol = &symbols[node - part_numbers.len()];
+
+            component.symbol_positions.push(symbol.get_position());
+            component.symbols.insert(symbol.get_value());
+        }
+    }
+
+    components
+}
+
+fn find(parents: &mut [usize], node: usize) -> usize {
+    let mut root = node;
+    while parents[root] != root {
+        root = parents[root];
+    }
+
+    // compress the path so that later lookups are direct
+    let mut current_node = node;
+    while parents[current_node] != root {
+        let next_node = parents[current_node];
+        parents[current_node] = root;
+        current_node = next_node;
+    }
+
+    root
+}
+
+fn union(parents: &mut [usize], first_node: usize, second_node: usize) {
+    let first_root = find(parents, first_node);
+    let second_root = find(parents, second_node);
+
+    if first_root != second_root {
+        parents[first_root.max(second_root)] = first_root.min(second_root);
+    }
+}
+
+pub fn to_dot(part_numbers: &[PartNumber], symbols: &[Symbol]) -> String {
+    let mut output = String::from("graph schematic {\n");
+
+    for part_number in part_numbers {
+        output.push_str(&format!(
+            "    n{} [shape=box, label=\"{} ({}, {}..{})\"];\n",
+            part_number.get_id(),
+            part_number.get_value(),
+            part_number.get_row_index(),
+            part_number.get_column_indices().start,
+            part_number.get_column_indices().end
+        ));
+    }
+
+    for (symbol_index, symbol) in symbols.iter().enumerate() {
+        let (row_index, column_index) = symbol.get_position();
+
+        output.push_str(&format!(
+            "    s{} [shape=circle, label=\"{} ({}, {})\"];\n",
+            symbol_index,
+            escape(symbol.get_value()),
+            row_index,
+            column_index
+        ));
+    }
+
+    for (symbol_index, symbol) in symbols.iter().enumerate() {
+        for part_number in symbol.get_adjacent_part_numbers() {
+            output.push_str(&format!(
+                "    s{} -- n{};\n",
+                symbol_index,
+                part_number.get_id()
+            ));
+        }
+    }
+
+    output.push_str("}\n");
+
+    output
+}
+
+fn escape(character: char) -> String {
+    match character {
+        '"' | '\\' => format!("\\{}", character),
+        c => c.to_string(),
+    }
+}
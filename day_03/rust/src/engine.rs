@@ -1,295 +1,961 @@
+use super::component::{self, Component};
+use super::render::{self, CellKind, RenderTarget};
+use super::rules::EngineRules;
+use std::{cmp::Ordering, collections::BTreeMap, ops::Range};
+
+#[derive(Clone)]
 enum Part {
     Empty,
     Digit(u32),
     Symbol(char),
+    // punctuation that is not a symbol under the current rules, kept so the schematic can be
+    // written back, but it never counts for adjacency
+    Inert(char),
 }
 
-impl TryFrom<char> for Part {
-    type Error = String;
-    fn try_from(value: char) -> Result<Self, Self::Error> {
+impl Part {
+    fn try_build(value: char, rules: &EngineRules) -> Result<Self, String> {
         match value {
-            '.' => Ok(Part::Empty),
+            c if rules.is_empty(c) => Ok(Part::Empty),
             n @ '0'..='9' => Ok(Part::Digit(n.to_digit(10).unwrap())),
-            c if c.is_ascii_punctuation() => Ok(Part::Symbol(c)),
+            c if rules.is_symbol(c) => Ok(Part::Symbol(c)),
+            c if c.is_ascii_punctuation() => Ok(Part::Inert(c)),
             _ => Err(format!("Cannot parse Part from {}", value)),
         }
     }
 }
 
-#[derive(PartialEq)]
-struct PartNumber {
-    value: u32,
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartNumber {
+    id: usize,
+    value: u64,
+    row_index: usize,
+    column_indices: Range<usize>,
+}
+
+impl PartNumber {
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn get_value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn get_row_index(&self) -> usize {
+        self.row_index
+    }
+
+    pub fn get_column_indices(&self) -> Range<usize> {
+        self.column_indices.clone()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Symbol<'a> {
+    value: char,
     row_index: usize,
-    column_indices: Vec<usize>,
+    column_index: usize,
+    adjacent_part_numbers: Vec<&'a PartNumber>,
+}
+
+impl<'a> Symbol<'a> {
+    pub fn get_value(&self) -> char {
+        self.value
+    }
+
+    pub fn get_position(&self) -> (usize, usize) {
+        (self.row_index, self.column_index)
+    }
+
+    pub fn get_adjacent_part_numbers(&self) -> &[&'a PartNumber] {
+        &self.adjacent_part_numbers
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RaggedRows {
+    Reject,
+    Pad,
 }
 
 pub struct Engine {
-    schematic: Vec<Vec<Part>>,
+    // cells are stored row by row, the part number ids use the same layout
+    schematic: Vec<Part>,
+    part_number_ids: Vec<Option<usize>>,
+    part_numbers: Vec<PartNumber>,
+    number_of_rows: usize,
+    number_of_columns: usize,
+    neighbour_offsets: Vec<(i64, i64)>,
+    rules: EngineRules,
+    // cached results, kept up to date by the editing methods
+    is_valid_part_number: Vec<bool>,
+    gear_ratios: BTreeMap<usize, Option<u64>>,
+    sum_of_part_numbers: u128,
+    sum_of_gear_ratios: u128,
+    number_of_overflowing_gear_ratios: usize,
 }
 
 impl Engine {
-    pub fn try_build(input: Vec<&str>) -> Option<Self> {
-        let mut schematic = Vec::new();
+    pub fn try_build(input: Vec<&str>, ragged_rows: RaggedRows) -> Result<Self, String> {
+        Self::try_build_with_rules(input, ragged_rows, EngineRules::default())
+    }
+
+    pub fn try_build_with_rules(
+        input: Vec<&str>,
+        ragged_rows: RaggedRows,
+        rules: EngineRules,
+    ) -> Result<Self, String> {
+        let number_of_rows = input.len();
+        let number_of_columns = input
+            .iter()
+            .map(|input_line| input_line.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut schematic = Vec::with_capacity(number_of_rows * number_of_columns);
+
+        for (row_index, input_line) in input.iter().enumerate() {
+            let mut row_length = 0;
 
-        for input_line in &input {
-            schematic.push(Vec::new());
-            let last_schematic_input = schematic.last_mut().unwrap();
             for character in input_line.chars() {
-                match Part::try_from(character) {
-                    Ok(part) => last_schematic_input.push(part),
+                match Part::try_build(character, &rules) {
+                    Ok(part) => schematic.push(part),
                     Err(part_error) => {
-                        println!("Cannot build engine schematic, {}", part_error);
-                        return None;
+                        return Err(format!("Cannot build engine schematic, {}", part_error))
+                    }
+                }
+                row_length += 1;
+            }
+
+            if row_length != number_of_columns {
+                match ragged_rows {
+                    RaggedRows::Reject => {
+                        return Err(format!(
+                            "Cannot build engine schematic, row {} has {} columns but expected {}",
+                            row_index, row_length, number_of_columns
+                        ))
                     }
+                    RaggedRows::Pad => schematic
+                        .resize_with(schematic.len() + number_of_columns - row_length, || {
+                            Part::Empty
+                        }),
                 }
             }
         }
 
-        Some(Engine { schematic })
+        let mut engine = Engine {
+            schematic,
+            part_number_ids: Vec::new(),
+            part_numbers: Vec::new(),
+            number_of_rows,
+            number_of_columns,
+            neighbour_offsets: Self::get_neighbour_offsets(&rules),
+            rules,
+            is_valid_part_number: Vec::new(),
+            gear_ratios: BTreeMap::new(),
+            sum_of_part_numbers: 0,
+            sum_of_gear_ratios: 0,
+            number_of_overflowing_gear_ratios: 0,
+        };
+        engine.try_recompute()?;
+
+        Ok(engine)
     }
 
-    pub fn get_valid_part_number_values(&self) -> Vec<u32> {
-        let symbol_positions = self.get_symbol_positions();
-
-        let mut adjacent_part_numbers = Vec::new();
+    fn get_neighbour_offsets(rules: &EngineRules) -> Vec<(i64, i64)> {
+        let neighbourhood = rules.get_neighbourhood();
+        let radius = neighbourhood.get_radius() as i64;
 
-        for symbol_position in symbol_positions {
-            let current_adjacent_part_numbers = self.get_adjacent_part_numbers(symbol_position);
+        let mut neighbour_offsets = Vec::new();
 
-            for part_number in current_adjacent_part_numbers {
-                if !adjacent_part_numbers.contains(&part_number) {
-                    adjacent_part_numbers.push(part_number);
+        for row_offset in -radius..=radius {
+            for column_offset in -radius..=radius {
+                if neighbourhood.contains_offset(row_offset, column_offset) {
+                    neighbour_offsets.push((row_offset, column_offset));
                 }
             }
         }
 
-        adjacent_part_numbers
-            .into_iter()
-            .map(|part_number| part_number.value)
-            .collect()
+        neighbour_offsets
     }
 
-    fn get_symbol_positions(&self) -> Vec<(usize, usize)> {
-        let mut symbol_positions = Vec::new();
+    fn try_recompute(&mut self) -> Result<(), String> {
+        let mut part_numbers = Vec::new();
+
+        for row_index in 0..self.number_of_rows {
+            part_numbers.extend(self.try_tokenize_row(row_index)?);
+        }
+
+        self.part_number_ids = vec![None; self.schematic.len()];
+        self.is_valid_part_number = vec![false; part_numbers.len()];
+        self.part_numbers = part_numbers;
+        self.renumber_part_numbers(0..self.part_numbers.len());
 
-        for (row_index, row) in self.schematic.iter().enumerate() {
-            for (column_index, part) in row.iter().enumerate() {
-                if let Part::Symbol(_) = part {
-                    symbol_positions.push((row_index, column_index));
+        self.gear_ratios.clear();
+        self.sum_of_part_numbers = 0;
+        self.sum_of_gear_ratios = 0;
+        self.number_of_overflowing_gear_ratios = 0;
+
+        self.refresh_region(0..self.number_of_rows, 0..self.number_of_columns);
+
+        Ok(())
+    }
+
+    fn try_tokenize_row(&self, row_index: usize) -> Result<Vec<PartNumber>, String> {
+        let mut part_numbers = Vec::new();
+        let mut current_part_number: Option<PartNumber> = None;
+
+        for column_index in 0..self.number_of_columns {
+            match (
+                &self.schematic[self.get_cell_index((row_index, column_index))],
+                current_part_number.as_mut(),
+            ) {
+                (Part::Digit(n), Some(part_number)) => {
+                    part_number.value = part_number
+                        .value
+                        .checked_mul(10)
+                        .and_then(|value| value.checked_add(*n as u64))
+                        .ok_or_else(|| {
+                            format!(
+                                "Cannot build engine schematic, part number in row {} starting at column {} does not fit into u64",
+                                row_index, part_number.column_indices.start
+                            )
+                        })?;
+                    part_number.column_indices.end = column_index + 1;
+                }
+                (Part::Digit(n), None) => {
+                    // ids are assigned once the part number is placed in the engine
+                    current_part_number = Some(PartNumber {
+                        id: 0,
+                        value: *n as u64,
+                        row_index,
+                        column_indices: column_index..column_index + 1,
+                    })
                 }
+                (_, Some(_)) => part_numbers.extend(current_part_number.take()),
+                (_, None) => (),
             }
         }
 
-        symbol_positions
+        part_numbers.extend(current_part_number);
+
+        Ok(part_numbers)
     }
 
-    fn get_adjacent_part_numbers(&self, position: (usize, usize)) -> Vec<PartNumber> {
-        let neighbour_positions = self.get_neighbour_positions(position);
+    fn renumber_part_numbers(&mut self, part_number_ids: Range<usize>) {
+        for part_number_id in part_number_ids {
+            let part_number = &mut self.part_numbers[part_number_id];
+            part_number.id = part_number_id;
 
-        let mut adjacent_part_numbers = Vec::new();
+            let row_start_cell_index = part_number.row_index * self.number_of_columns;
+            for column_index in part_number.column_indices.clone() {
+                self.part_number_ids[row_start_cell_index + column_index] = Some(part_number_id);
+            }
+        }
+    }
 
-        for (row_index, column_index) in neighbour_positions {
-            if let Some(Part::Digit(_)) = self.schematic.get(row_index).unwrap().get(column_index) {
-                let part_number = self.get_part_number(row_index, column_index);
+    fn get_part_number_id_range(&self, rows: Range<usize>) -> Range<usize> {
+        self.part_numbers
+            .partition_point(|part_number| part_number.row_index < rows.start)
+            ..self
+                .part_numbers
+                .partition_point(|part_number| part_number.row_index < rows.end)
+    }
+
+    // recomputes the cached results of everything within the neighbourhood radius of the region
+    fn refresh_region(&mut self, rows: Range<usize>, columns: Range<usize>) {
+        let radius = self.rules.get_neighbourhood().get_radius();
+        let rows = rows.start.saturating_sub(radius)..(rows.end + radius).min(self.number_of_rows);
+        let columns = columns.start.saturating_sub(radius)
+            ..(columns.end + radius).min(self.number_of_columns);
+
+        for part_number_id in self.get_part_number_id_range(rows.clone()) {
+            let part_number = &self.part_numbers[part_number_id];
+            if part_number.column_indices.start >= columns.end
+                || part_number.column_indices.end <= columns.start
+            {
+                continue;
+            }
+
+            let is_valid_part_number = self.is_adjacent_to_symbol(part_number);
+            if is_valid_part_number != self.is_valid_part_number[part_number_id] {
+                self.set_valid_part_number(part_number_id, is_valid_part_number);
+            }
+        }
 
-                if !adjacent_part_numbers.contains(&part_number) {
-                    adjacent_part_numbers.push(part_number);
+        for row_index in rows {
+            for column_index in columns.clone() {
+                let cell_index = self.get_cell_index((row_index, column_index));
+
+                let previous_gear_ratio = match self.get_gear_ratio((row_index, column_index)) {
+                    Some(gear_ratio) => {
+                        self.add_gear_ratio(gear_ratio);
+                        self.gear_ratios.insert(cell_index, gear_ratio)
+                    }
+                    None => self.gear_ratios.remove(&cell_index),
+                };
+
+                if let Some(previous_gear_ratio) = previous_gear_ratio {
+                    self.remove_gear_ratio(previous_gear_ratio);
                 }
             }
         }
+    }
 
-        adjacent_part_numbers
+    fn is_adjacent_to_symbol(&self, part_number: &PartNumber) -> bool {
+        part_number.column_indices.clone().any(|column_index| {
+            self.get_neighbour_positions((part_number.row_index, column_index))
+                .any(|neighbour_position| {
+                    matches!(
+                        self.schematic[self.get_cell_index(neighbour_position)],
+                        Part::Symbol(_)
+                    )
+                })
+        })
     }
 
-    fn get_neighbour_positions(&self, position: (usize, usize)) -> Vec<(usize, usize)> {
-        let possible_neighbour_positions = self.get_all_possible_neighbour_positions(position);
+    fn set_valid_part_number(&mut self, part_number_id: usize, is_valid_part_number: bool) {
+        let value = self.part_numbers[part_number_id].value as u128;
+
+        if is_valid_part_number {
+            self.sum_of_part_numbers += value;
+        } else {
+            self.sum_of_part_numbers -= value;
+        }
 
-        let mut neighbour_positions = Vec::new();
+        self.is_valid_part_number[part_number_id] = is_valid_part_number;
+    }
 
-        for neighbour_position in possible_neighbour_positions {
-            if self.is_inside_schematic(neighbour_position) {
-                neighbour_positions
-                    .push((neighbour_position.0 as usize, neighbour_position.1 as usize));
+    // None if the symbol is no gear, Some(None) if the gear ratio does not fit into u64
+    fn get_gear_ratio(&self, position: (usize, usize)) -> Option<Option<u64>> {
+        match self.schematic[self.get_cell_index(position)] {
+            Part::Symbol(symbol) if self.rules.is_gear(symbol) => {
+                let adjacent_part_number_ids = self.get_adjacent_part_number_ids(position);
+
+                if self
+                    .rules
+                    .is_gear_part_number_count(adjacent_part_number_ids.len())
+                {
+                    Some(adjacent_part_number_ids.iter().try_fold(
+                        1u64,
+                        |gear_ratio, part_number_id| {
+                            gear_ratio.checked_mul(self.part_numbers[*part_number_id].value)
+                        },
+                    ))
+                } else {
+                    None
+                }
             }
+            _ => None,
         }
+    }
 
-        neighbour_positions
+    fn add_gear_ratio(&mut self, gear_ratio: Option<u64>) {
+        match gear_ratio {
+            Some(gear_ratio) => self.sum_of_gear_ratios += gear_ratio as u128,
+            None => self.number_of_overflowing_gear_ratios += 1,
+        }
     }
 
-    fn get_all_possible_neighbour_positions(&self, position: (usize, usize)) -> Vec<(i32, i32)> {
-        let mut possible_neighbour_positions = Vec::new();
+    fn remove_gear_ratio(&mut self, gear_ratio: Option<u64>) {
+        match gear_ratio {
+            Some(gear_ratio) => self.sum_of_gear_ratios -= gear_ratio as u128,
+            None => self.number_of_overflowing_gear_ratios -= 1,
+        }
+    }
 
-        for row_index in [
-            position.0 as i32 - 1,
-            position.0 as i32,
-            position.0 as i32 + 1,
-        ] {
-            for column_index in [
-                position.1 as i32 - 1,
-                position.1 as i32,
-                position.1 as i32 + 1,
-            ] {
-                if !self.is_same_position(position, (row_index, column_index)) {
-                    possible_neighbour_positions.push((row_index, column_index));
+    pub fn try_set_cell(
+        &mut self,
+        position: (usize, usize),
+        character: char,
+    ) -> Result<(), String> {
+        if !self.is_inside_schematic((position.0 as i64, position.1 as i64)) {
+            return Err(format!(
+                "Cannot set cell at row {} column {}, it is outside of the schematic",
+                position.0, position.1
+            ));
+        }
+
+        let part = Part::try_build(character, &self.rules)?;
+        let cell_index = self.get_cell_index(position);
+        let previous_part = std::mem::replace(&mut self.schematic[cell_index], part);
+
+        let row_part_numbers = match self.try_tokenize_row(position.0) {
+            Ok(row_part_numbers) => row_part_numbers,
+            Err(tokenize_error) => {
+                self.schematic[cell_index] = previous_part;
+                return Err(tokenize_error);
+            }
+        };
+
+        let changed_columns = self.replace_row_part_numbers(position.0, row_part_numbers);
+
+        let columns = changed_columns.into_iter().fold(
+            position.1..position.1 + 1,
+            |columns, changed_columns| {
+                columns.start.min(changed_columns.start)..columns.end.max(changed_columns.end)
+            },
+        );
+        self.refresh_region(position.0..position.0 + 1, columns);
+
+        Ok(())
+    }
+
+    // returns the column spans of the part numbers that were removed or added
+    fn replace_row_part_numbers(
+        &mut self,
+        row_index: usize,
+        row_part_numbers: Vec<PartNumber>,
+    ) -> Vec<Range<usize>> {
+        let part_number_id_range = self.get_part_number_id_range(row_index..row_index + 1);
+        let mut changed_columns = Vec::new();
+
+        let is_same_part_number = |first: &PartNumber, second: &PartNumber| {
+            first.value == second.value && first.column_indices == second.column_indices
+        };
+
+        let mut is_valid_part_number = Vec::with_capacity(row_part_numbers.len());
+        for part_number in &row_part_numbers {
+            match part_number_id_range.clone().find(|part_number_id| {
+                is_same_part_number(&self.part_numbers[*part_number_id], part_number)
+            }) {
+                Some(part_number_id) => {
+                    is_valid_part_number.push(self.is_valid_part_number[part_number_id])
+                }
+                None => {
+                    is_valid_part_number.push(false);
+                    changed_columns.push(part_number.column_indices.clone());
                 }
             }
         }
 
-        possible_neighbour_positions
-    }
+        for part_number_id in part_number_id_range.clone() {
+            let part_number = self.part_numbers[part_number_id].clone();
 
-    fn is_inside_schematic(&self, position: (i32, i32)) -> bool {
-        let max_row_index = self.schematic.len() - 1;
-        let max_column_index = self.schematic.first().unwrap().len() - 1;
+            if !row_part_numbers
+                .iter()
+                .any(|row_part_number| is_same_part_number(&part_number, row_part_number))
+            {
+                changed_columns.push(part_number.column_indices.clone());
 
-        if (position.0 >= 0 && position.0 <= max_column_index as i32)
-            && (position.1 >= 0 && position.1 <= max_row_index as i32)
-        {
-            true
+                if self.is_valid_part_number[part_number_id] {
+                    self.set_valid_part_number(part_number_id, false);
+                }
+            }
+
+            let row_start_cell_index = part_number.row_index * self.number_of_columns;
+            for column_index in part_number.column_indices {
+                self.part_number_ids[row_start_cell_index + column_index] = None;
+            }
+        }
+
+        let is_same_length = part_number_id_range.len() == row_part_numbers.len();
+
+        self.part_numbers
+            .splice(part_number_id_range.clone(), row_part_numbers);
+        self.is_valid_part_number
+            .splice(part_number_id_range.clone(), is_valid_part_number);
+
+        // ids after this row only change when the row gained or lost part numbers
+        if is_same_length {
+            self.renumber_part_numbers(part_number_id_range);
         } else {
-            false
+            self.renumber_part_numbers(part_number_id_range.start..self.part_numbers.len());
         }
-    }
 
-    fn is_same_position(&self, position_usize: (usize, usize), position_i32: (i32, i32)) -> bool {
-        position_usize.0 as i32 == position_i32.0 && position_usize.1 as i32 == position_i32.1
+        changed_columns
     }
 
-    fn get_part_number(&self, row_index: usize, start_column_index: usize) -> PartNumber {
-        let (part_number_value, part_number_value_digit_indices) =
-            self.get_part_number_value_and_digit_indices(row_index, start_column_index);
+    pub fn try_insert_row(&mut self, row_index: usize) -> Result<(), String> {
+        if row_index > self.number_of_rows {
+            return Err(format!(
+                "Cannot insert row {}, schematic only has {} rows",
+                row_index, self.number_of_rows
+            ));
+        }
 
-        PartNumber {
-            value: part_number_value,
-            row_index: row_index,
-            column_indices: part_number_value_digit_indices,
+        let start_cell_index = row_index * self.number_of_columns;
+        self.schematic.splice(
+            start_cell_index..start_cell_index,
+            (0..self.number_of_columns).map(|_| Part::Empty),
+        );
+        self.part_number_ids.splice(
+            start_cell_index..start_cell_index,
+            vec![None; self.number_of_columns],
+        );
+
+        for part_number in &mut self.part_numbers {
+            if part_number.row_index >= row_index {
+                part_number.row_index += 1;
+            }
         }
+
+        self.shift_gear_ratios(start_cell_index, self.number_of_columns as i64);
+        self.number_of_rows += 1;
+
+        self.refresh_region(row_index..row_index + 1, 0..self.number_of_columns);
+
+        Ok(())
     }
 
-    fn get_part_number_value_and_digit_indices(
-        &self,
-        row_index: usize,
-        start_column_index: usize,
-    ) -> (u32, Vec<usize>) {
-        let (first_part_number_value_digits, first_part_number_value_digit_indices) =
-            self.get_first_part_number_digits_and_indices(row_index, start_column_index);
-        let (last_part_number_value_digits, last_part_number_value_digit_indices) =
-            self.get_last_part_number_digits_and_indices(row_index, start_column_index);
-
-        let part_number_value_digits = vec![
-            first_part_number_value_digits,
-            last_part_number_value_digits,
-        ]
-        .concat();
-        let part_number_value_digit_indices = vec![
-            first_part_number_value_digit_indices,
-            last_part_number_value_digit_indices,
-        ]
-        .concat();
-
-        let part_number_value = self.get_part_number_value(part_number_value_digits);
-
-        (part_number_value, part_number_value_digit_indices)
-    }
-
-    fn get_first_part_number_digits_and_indices(
-        &self,
-        row_index: usize,
-        start_column_index: usize,
-    ) -> (Vec<u32>, Vec<usize>) {
-        let row = self.schematic.get(row_index).unwrap();
-        let mut first_part_number_value_digits = Vec::new();
-        let mut first_part_number_value_digit_indices = Vec::new();
-
-        let mut first_digit_column_index = start_column_index;
-        while let Some(Part::Digit(n)) = row.get(first_digit_column_index) {
-            first_part_number_value_digits.push(*n);
-            first_part_number_value_digit_indices.push(first_digit_column_index);
-
-            if first_digit_column_index == 0 {
-                break;
+    pub fn try_delete_row(&mut self, row_index: usize) -> Result<(), String> {
+        if row_index >= self.number_of_rows {
+            return Err(format!(
+                "Cannot delete row {}, schematic only has {} rows",
+                row_index, self.number_of_rows
+            ));
+        }
+
+        let part_number_id_range = self.get_part_number_id_range(row_index..row_index + 1);
+        for part_number_id in part_number_id_range.clone() {
+            if self.is_valid_part_number[part_number_id] {
+                self.set_valid_part_number(part_number_id, false);
             }
+        }
+        self.part_numbers.drain(part_number_id_range.clone());
+        self.is_valid_part_number
+            .drain(part_number_id_range.clone());
+
+        let cell_index_range =
+            row_index * self.number_of_columns..(row_index + 1) * self.number_of_columns;
+        let removed_gear_ratios: Vec<Option<u64>> = self
+            .gear_ratios
+            .range(cell_index_range.clone())
+            .map(|(_, gear_ratio)| *gear_ratio)
+            .collect();
+        for gear_ratio in removed_gear_ratios {
+            self.remove_gear_ratio(gear_ratio);
+        }
+        self.gear_ratios
+            .retain(|cell_index, _| !cell_index_range.contains(cell_index));
 
-            first_digit_column_index -= 1;
+        self.schematic.drain(cell_index_range.clone());
+        self.part_number_ids.drain(cell_index_range.clone());
+
+        for part_number in &mut self.part_numbers {
+            if part_number.row_index > row_index {
+                part_number.row_index -= 1;
+            }
         }
 
-        // reverse in order to have right sorting of digits
-        first_part_number_value_digits.reverse();
-        first_part_number_value_digit_indices.reverse();
+        self.shift_gear_ratios(cell_index_range.end, -(self.number_of_columns as i64));
+        self.number_of_rows -= 1;
+        self.renumber_part_numbers(part_number_id_range.start..self.part_numbers.len());
 
-        (
-            first_part_number_value_digits,
-            first_part_number_value_digit_indices,
-        )
+        self.refresh_region(
+            row_index.saturating_sub(1)..(row_index + 1).min(self.number_of_rows),
+            0..self.number_of_columns,
+        );
+
+        Ok(())
     }
 
-    fn get_last_part_number_digits_and_indices(
-        &self,
-        row_index: usize,
-        start_column_index: usize,
-    ) -> (Vec<u32>, Vec<usize>) {
-        let row = self.schematic.get(row_index).unwrap();
-        let mut last_part_number_value_digits = Vec::new();
-        let mut last_part_number_value_digit_indices = Vec::new();
-
-        let mut last_digit_column_index = start_column_index + 1;
-        while let Some(Part::Digit(n)) = row.get(last_digit_column_index) {
-            last_part_number_value_digits.push(*n);
-            last_part_number_value_digit_indices.push(last_digit_column_index);
-
-            if last_digit_column_index == row.len() - 1 {
-                break;
+    fn shift_gear_ratios(&mut self, start_cell_index: usize, offset: i64) {
+        let shifted_gear_ratios = self.gear_ratios.split_off(&start_cell_index);
+
+        for (cell_index, gear_ratio) in shifted_gear_ratios {
+            self.gear_ratios
+                .insert((cell_index as i64 + offset) as usize, gear_ratio);
+        }
+    }
+
+    // a column touches every row, so every row is tokenized again, but only the part numbers
+    // that changed and the band around the column are refreshed
+    pub fn try_insert_column(&mut self, column_index: usize) -> Result<(), String> {
+        if column_index > self.number_of_columns {
+            return Err(format!(
+                "Cannot insert column {}, schematic only has {} columns",
+                column_index, self.number_of_columns
+            ));
+        }
+
+        // rebuilt row by row, so rows without any columns still get their new cell
+        let mut schematic = Vec::with_capacity(self.number_of_rows * (self.number_of_columns + 1));
+        for row_index in 0..self.number_of_rows {
+            let row_start = row_index * self.number_of_columns;
+
+            schematic.extend_from_slice(&self.schematic[row_start..row_start + column_index]);
+            schematic.push(Part::Empty);
+            schematic.extend_from_slice(
+                &self.schematic[row_start + column_index..row_start + self.number_of_columns],
+            );
+        }
+
+        self.try_replace_columns(
+            schematic,
+            self.number_of_columns + 1,
+            |previous_column_index| {
+                if previous_column_index < column_index {
+                    Some(previous_column_index)
+                } else {
+                    Some(previous_column_index + 1)
+                }
+            },
+        )?;
+
+        self.refresh_region(0..self.number_of_rows, column_index..column_index + 1);
+
+        Ok(())
+    }
+
+    pub fn try_delete_column(&mut self, column_index: usize) -> Result<(), String> {
+        if column_index >= self.number_of_columns {
+            return Err(format!(
+                "Cannot delete column {}, schematic only has {} columns",
+                column_index, self.number_of_columns
+            ));
+        }
+
+        let number_of_columns = self.number_of_columns;
+        let schematic = self
+            .schematic
+            .iter()
+            .enumerate()
+            .filter(|(cell_index, _)| cell_index % number_of_columns != column_index)
+            .map(|(_, part)| part.clone())
+            .collect();
+
+        // removing a column can join digits into a part number that does not fit
+        self.try_replace_columns(schematic, number_of_columns - 1, |previous_column_index| {
+            match previous_column_index.cmp(&column_index) {
+                Ordering::Less => Some(previous_column_index),
+                Ordering::Equal => None,
+                Ordering::Greater => Some(previous_column_index - 1),
             }
+        })?;
+
+        self.refresh_region(
+            0..self.number_of_rows,
+            column_index.saturating_sub(1)..(column_index + 1).min(self.number_of_columns),
+        );
 
-            last_digit_column_index += 1;
+        Ok(())
+    }
+
+    // swaps in the schematic of a column edit, get_column_index maps a previous column to its
+    // new one or to None if it was removed, the engine is left unchanged on error
+    fn try_replace_columns(
+        &mut self,
+        schematic: Vec<Part>,
+        number_of_columns: usize,
+        get_column_index: impl Fn(usize) -> Option<usize>,
+    ) -> Result<(), String> {
+        let previous_schematic = std::mem::replace(&mut self.schematic, schematic);
+        let previous_number_of_columns =
+            std::mem::replace(&mut self.number_of_columns, number_of_columns);
+
+        let mut rows_part_numbers = Vec::with_capacity(self.number_of_rows);
+        for row_index in 0..self.number_of_rows {
+            match self.try_tokenize_row(row_index) {
+                Ok(row_part_numbers) => rows_part_numbers.push(row_part_numbers),
+                Err(tokenize_error) => {
+                    self.schematic = previous_schematic;
+                    self.number_of_columns = previous_number_of_columns;
+                    return Err(tokenize_error);
+                }
+            }
         }
 
-        (
-            last_part_number_value_digits,
-            last_part_number_value_digit_indices,
-        )
+        // a part number is kept if it only moved, splitting it or removing one of its digits
+        // changes its span
+        let is_same_part_number = |previous: &PartNumber, part_number: &PartNumber| {
+            let column_indices = &previous.column_indices;
+
+            previous.value == part_number.value
+                && get_column_index(column_indices.start) == Some(part_number.column_indices.start)
+                && get_column_index(column_indices.end - 1)
+                    == Some(part_number.column_indices.end - 1)
+                && part_number.column_indices.len() == column_indices.len()
+        };
+
+        let mut part_numbers = Vec::with_capacity(self.part_numbers.len());
+        let mut is_valid_part_number = Vec::with_capacity(self.part_numbers.len());
+        let mut changed_regions = Vec::new();
+
+        for (row_index, row_part_numbers) in rows_part_numbers.into_iter().enumerate() {
+            let part_number_id_range = self.get_part_number_id_range(row_index..row_index + 1);
+
+            for part_number in &row_part_numbers {
+                match part_number_id_range.clone().find(|part_number_id| {
+                    is_same_part_number(&self.part_numbers[*part_number_id], part_number)
+                }) {
+                    Some(part_number_id) => {
+                        is_valid_part_number.push(self.is_valid_part_number[part_number_id])
+                    }
+                    None => {
+                        is_valid_part_number.push(false);
+                        changed_regions.push((row_index, part_number.column_indices.clone()));
+                    }
+                }
+            }
+
+            for part_number_id in part_number_id_range {
+                let previous = self.part_numbers[part_number_id].clone();
+
+                if row_part_numbers
+                    .iter()
+                    .any(|part_number| is_same_part_number(&previous, part_number))
+                {
+                    continue;
+                }
+
+                if self.is_valid_part_number[part_number_id] {
+                    self.set_valid_part_number(part_number_id, false);
+                }
+
+                // gears next to the digits that are left still counted the removed part number
+                let remaining_column_indices: Vec<usize> = previous
+                    .column_indices
+                    .filter_map(&get_column_index)
+                    .collect();
+                if let (Some(first), Some(last)) = (
+                    remaining_column_indices.first(),
+                    remaining_column_indices.last(),
+                ) {
+                    changed_regions.push((row_index, *first..*last + 1));
+                }
+            }
+
+            part_numbers.extend(row_part_numbers);
+        }
+
+        self.part_number_ids = vec![None; self.schematic.len()];
+        self.part_numbers = part_numbers;
+        self.is_valid_part_number = is_valid_part_number;
+        self.renumber_part_numbers(0..self.part_numbers.len());
+
+        for (cell_index, gear_ratio) in std::mem::take(&mut self.gear_ratios) {
+            match get_column_index(cell_index % previous_number_of_columns) {
+                Some(column_index) => {
+                    let cell_index = self
+                        .get_cell_index((cell_index / previous_number_of_columns, column_index));
+                    self.gear_ratios.insert(cell_index, gear_ratio);
+                }
+                None => self.remove_gear_ratio(gear_ratio),
+            }
+        }
+
+        for (row_index, column_indices) in changed_regions {
+            self.refresh_region(row_index..row_index + 1, column_indices);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_rows(&self) -> Vec<String> {
+        self.get_classified_cells()
+            .into_iter()
+            .map(|row| row.into_iter().map(|(character, _)| character).collect())
+            .collect()
+    }
+
+    pub fn get_valid_part_number_values(&self) -> Vec<u64> {
+        self.part_numbers
+            .iter()
+            .zip(&self.is_valid_part_number)
+            .filter(|(_, is_valid_part_number)| **is_valid_part_number)
+            .map(|(part_number, _)| part_number.value)
+            .collect()
+    }
+
+    pub fn get_sum_of_part_numbers(&self) -> Result<u64, String> {
+        u64::try_from(self.sum_of_part_numbers)
+            .map_err(|_| String::from("Sum of part numbers does not fit into u64"))
+    }
+
+    pub fn get_part_numbers(&self) -> &[PartNumber] {
+        &self.part_numbers
     }
 
-    fn get_part_number_value(&self, part_number_value_digits: Vec<u32>) -> u32 {
-        // calculate part number value from digits
-        let mut part_number_value = 0;
-        for (index, part_number_digit) in part_number_value_digits.into_iter().rev().enumerate() {
-            part_number_value += part_number_digit * (u32::pow(10, index as u32));
+    pub fn get_orphan_part_numbers(&self) -> Vec<&PartNumber> {
+        self.part_numbers
+            .iter()
+            .zip(&self.is_valid_part_number)
+            .filter(|(_, is_valid_part_number)| !**is_valid_part_number)
+            .map(|(part_number, _)| part_number)
+            .collect()
+    }
+
+    pub fn get_symbols(&self) -> Vec<Symbol<'_>> {
+        let mut symbols = Vec::new();
+
+        for symbol_position in self.get_symbol_positions() {
+            if let Part::Symbol(value) = self.schematic[self.get_cell_index(symbol_position)] {
+                symbols.push(Symbol {
+                    value,
+                    row_index: symbol_position.0,
+                    column_index: symbol_position.1,
+                    adjacent_part_numbers: self
+                        .get_adjacent_part_number_ids(symbol_position)
+                        .into_iter()
+                        .map(|part_number_id| &self.part_numbers[part_number_id])
+                        .collect(),
+                });
+            }
         }
 
-        part_number_value
+        symbols
     }
 
-    pub fn get_gear_ratios(&self) -> Vec<u32> {
-        let gear_positions = self.get_gear_positions();
+    // the part number covering the cell itself counts as touching as well
+    pub fn get_part_numbers_touching(&self, position: (usize, usize)) -> Vec<&PartNumber> {
+        if !self.is_inside_schematic((position.0 as i64, position.1 as i64)) {
+            return Vec::new();
+        }
+
+        let mut part_number_ids = self.get_adjacent_part_number_ids(position);
 
-        let mut gear_ratios: Vec<u32> = Vec::new();
+        if let Some(part_number_id) = self.part_number_ids[self.get_cell_index(position)] {
+            if !part_number_ids.contains(&part_number_id) {
+                part_number_ids.push(part_number_id);
+            }
+        }
 
-        for gear_position in gear_positions {
-            let adjacent_part_numbers = self.get_adjacent_part_numbers(gear_position);
+        part_number_ids.sort();
 
-            if adjacent_part_numbers.len() == 2 {
-                gear_ratios.push(
-                    adjacent_part_numbers.first().unwrap().value
-                        * adjacent_part_numbers.last().unwrap().value,
-                );
+        part_number_ids
+            .into_iter()
+            .map(|part_number_id| &self.part_numbers[part_number_id])
+            .collect()
+    }
+
+    fn get_symbol_positions(&self) -> Vec<(usize, usize)> {
+        let mut symbol_positions = Vec::new();
+
+        for (cell_index, part) in self.schematic.iter().enumerate() {
+            if let Part::Symbol(_) = part {
+                symbol_positions.push(self.get_position(cell_index));
             }
         }
 
-        gear_ratios
+        symbol_positions
     }
 
-    fn get_gear_positions(&self) -> Vec<(usize, usize)> {
-        let mut gear_positions = Vec::new();
+    fn get_adjacent_part_number_ids(&self, position: (usize, usize)) -> Vec<usize> {
+        let mut adjacent_part_number_ids = Vec::new();
 
-        for (row_index, row) in self.schematic.iter().enumerate() {
-            for (column_index, part) in row.iter().enumerate() {
-                if let Part::Symbol('*') = part {
-                    gear_positions.push((row_index, column_index));
+        for neighbour_position in self.get_neighbour_positions(position) {
+            if let Some(part_number_id) =
+                self.part_number_ids[self.get_cell_index(neighbour_position)]
+            {
+                if !adjacent_part_number_ids.contains(&part_number_id) {
+                    adjacent_part_number_ids.push(part_number_id);
                 }
             }
         }
 
-        gear_positions
+        adjacent_part_number_ids
+    }
+
+    fn get_neighbour_positions(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.neighbour_offsets
+            .iter()
+            .map(move |(row_offset, column_offset)| {
+                (
+                    position.0 as i64 + row_offset,
+                    position.1 as i64 + column_offset,
+                )
+            })
+            .filter(|neighbour_position| self.is_inside_schematic(*neighbour_position))
+            .map(|neighbour_position| {
+                (neighbour_position.0 as usize, neighbour_position.1 as usize)
+            })
+    }
+
+    fn is_inside_schematic(&self, position: (i64, i64)) -> bool {
+        (position.0 >= 0 && (position.0 as usize) < self.number_of_rows)
+            && (position.1 >= 0 && (position.1 as usize) < self.number_of_columns)
+    }
+
+    fn get_cell_index(&self, position: (usize, usize)) -> usize {
+        position.0 * self.number_of_columns + position.1
+    }
+
+    fn get_position(&self, cell_index: usize) -> (usize, usize) {
+        (
+            cell_index / self.number_of_columns,
+            cell_index % self.number_of_columns,
+        )
+    }
+
+    pub fn get_gear_ratios(&self) -> Result<Vec<u64>, String> {
+        let mut gear_ratios: Vec<u64> = Vec::new();
+
+        for (cell_index, gear_ratio) in &self.gear_ratios {
+            match gear_ratio {
+                Some(gear_ratio) => gear_ratios.push(*gear_ratio),
+                None => {
+                    let gear_position = self.get_position(*cell_index);
+                    return Err(format!(
+                        "Gear ratio at row {} column {} does not fit into u64",
+                        gear_position.0, gear_position.1
+                    ));
+                }
+            }
+        }
+
+        Ok(gear_ratios)
+    }
+
+    pub fn get_sum_of_gear_ratios(&self) -> Result<u64, String> {
+        if self.number_of_overflowing_gear_ratios > 0 {
+            return Err(format!(
+                "{} gear ratios do not fit into u64",
+                self.number_of_overflowing_gear_ratios
+            ));
+        }
+
+        u64::try_from(self.sum_of_gear_ratios)
+            .map_err(|_| String::from("Sum of gear ratios does not fit into u64"))
+    }
+
+    pub fn get_components(&self) -> Vec<Component> {
+        component::get_components(&self.part_numbers, &self.get_symbols())
+    }
+
+    pub fn to_dot(&self) -> String {
+        component::to_dot(&self.part_numbers, &self.get_symbols())
+    }
+
+    pub fn render(&self, target: RenderTarget) -> String {
+        render::render(&self.get_classified_cells(), target)
+    }
+
+    fn get_classified_cells(&self) -> Vec<Vec<(char, CellKind)>> {
+        let mut cells = Vec::with_capacity(self.number_of_rows);
+
+        for row_index in 0..self.number_of_rows {
+            let mut row = Vec::with_capacity(self.number_of_columns);
+
+            for column_index in 0..self.number_of_columns {
+                let cell_index = self.get_cell_index((row_index, column_index));
+
+                row.push(match &self.schematic[cell_index] {
+                    Part::Empty => (self.rules.get_empty(), CellKind::Empty),
+                    Part::Inert(character) => (*character, CellKind::Inert),
+                    Part::Digit(n) => (
+                        char::from_digit(*n, 10).unwrap(),
+                        match self.part_number_ids[cell_index] {
+                            Some(part_number_id) if self.is_valid_part_number[part_number_id] => {
+                                CellKind::PartNumber
+                            }
+                            _ => CellKind::OrphanPartNumber,
+                        },
+                    ),
+                    Part::Symbol(symbol) if self.gear_ratios.contains_key(&cell_index) => {
+                        (*symbol, CellKind::Gear)
+                    }
+                    Part::Symbol(symbol) => (*symbol, CellKind::Symbol),
+                });
+            }
+
+            cells.push(row);
+        }
+
+        cells
     }
 }
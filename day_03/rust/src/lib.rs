@@ -1,52 +1,73 @@
+mod component;
 mod engine;
+mod render;
+mod rules;
 
 use std::fs;
 
-use engine::Engine;
+pub use component::Component;
+pub use engine::{Engine, PartNumber, RaggedRows, Symbol};
+pub use render::{CellKind, RenderTarget};
+pub use rules::{EngineRules, Neighbourhood};
 
-pub fn part_1(file_name: &str) -> u32 {
-    let engine = try_get_engine(file_name);
+pub fn part_1(file_name: &str) -> u64 {
+    match try_get_engine(file_name).and_then(get_sum_of_part_numbers) {
+        Ok(sum_of_part_numbers) => sum_of_part_numbers,
+        Err(engine_error) => {
+            println!("Failed to get engine, {}", engine_error);
+            0
+        }
+    }
+}
 
-    if let Some(engine) = engine {
-        get_sum_of_part_numbers(engine)
-    } else {
-        println!("Failed to get engine");
-        0
+pub fn part_2(file_name: &str) -> u64 {
+    match try_get_engine(file_name).and_then(get_sum_of_gear_ratios) {
+        Ok(sum_of_gear_ratios) => sum_of_gear_ratios,
+        Err(engine_error) => {
+            println!("Failed to get engine, {}", engine_error);
+            0
+        }
     }
 }
 
-pub fn part_2(file_name: &str) -> u32 {
-    let engine = try_get_engine(file_name);
+pub fn render(file_name: &str, target: RenderTarget) -> Result<String, String> {
+    try_get_engine(file_name).map(|engine| engine.render(target))
+}
 
-    if let Some(engine) = engine {
-        get_sum_of_gear_ratios(engine)
-    } else {
-        println!("Failed to get engine");
-        0
-    }
+pub fn to_dot(file_name: &str) -> Result<String, String> {
+    try_get_engine(file_name).map(|engine| engine.to_dot())
 }
 
-fn try_get_engine(file_name: &str) -> Option<Engine> {
+fn try_get_engine(file_name: &str) -> Result<Engine, String> {
     let file_content =
         fs::read_to_string(file_name).expect("input file should be located in input folder");
-    let file_lines: Vec<&str> = file_content
-        .split('\n')
-        .map(|file_line| file_line.trim())
-        .collect();
 
-    Engine::try_build(file_lines)
+    Engine::try_build(get_schematic_lines(&file_content), RaggedRows::Reject)
 }
 
-fn get_sum_of_part_numbers(engine: Engine) -> u32 {
-    let part_numbers = engine.get_valid_part_number_values();
+// only the empty lines at the end are dropped, empty rows inside the schematic are ragged rows
+fn get_schematic_lines(file_content: &str) -> Vec<&str> {
+    let mut file_lines: Vec<&str> = file_content
+        .lines()
+        .map(|file_line| file_line.trim())
+        .collect();
+
+    while file_lines
+        .last()
+        .is_some_and(|file_line| file_line.is_empty())
+    {
+        file_lines.pop();
+    }
 
-    part_numbers.iter().sum()
+    file_lines
 }
 
-fn get_sum_of_gear_ratios(engine: Engine) -> u32 {
-    let gear_ratios = engine.get_gear_ratios();
+fn get_sum_of_part_numbers(engine: Engine) -> Result<u64, String> {
+    engine.get_sum_of_part_numbers()
+}
 
-    gear_ratios.iter().sum()
+fn get_sum_of_gear_ratios(engine: Engine) -> Result<u64, String> {
+    engine.get_sum_of_gear_ratios()
 }
 
 #[cfg(test)]
@@ -64,4 +85,322 @@ mod tests {
         let result = part_2("../input/test_input.txt");
         assert_eq!(result, 467835);
     }
+
+    #[test]
+    fn test_wide_schematic() {
+        let engine =
+            Engine::try_build(vec!["12.......*34", "..*.....5..."], RaggedRows::Reject).unwrap();
+        assert_eq!(get_sum_of_part_numbers(engine), Ok(12 + 34 + 5));
+    }
+
+    #[test]
+    fn test_tall_schematic() {
+        let engine = Engine::try_build(
+            vec!["1.", ".*", "..", "..", "7.", "*2", ".."],
+            RaggedRows::Reject,
+        )
+        .unwrap();
+        assert_eq!(get_sum_of_part_numbers(engine), Ok(1 + 7 + 2));
+
+        let engine =
+            Engine::try_build(vec!["3.", ".*", "..", "..", "7.", "*2"], RaggedRows::Reject)
+                .unwrap();
+        assert_eq!(get_sum_of_gear_ratios(engine), Ok(14));
+    }
+
+    #[test]
+    fn test_input_rules() {
+        let file_content = fs::read_to_string("../input/test_input.txt").unwrap();
+        let input: Vec<&str> = file_content.lines().collect();
+
+        let rules = EngineRules::default().with_symbols(vec!['*', '#']);
+        let engine =
+            Engine::try_build_with_rules(input.clone(), RaggedRows::Reject, rules).unwrap();
+        assert_eq!(get_sum_of_part_numbers(engine), Ok(4361 - 592 - 664));
+
+        // punctuation that is no symbol is kept but does not count for adjacency
+        let rules = EngineRules::default().with_symbols(vec!['*']);
+        let mut engine =
+            Engine::try_build_with_rules(vec!["1#2*"], RaggedRows::Reject, rules).unwrap();
+        assert_eq!(engine.get_rows(), vec!["1#2*"]);
+        assert_eq!(engine.get_sum_of_part_numbers(), Ok(2));
+        engine.try_set_cell((0, 2), '$').unwrap();
+        assert_eq!(engine.get_rows(), vec!["1#$*"]);
+        assert_eq!(engine.get_sum_of_part_numbers(), Ok(0));
+
+        let rules = EngineRules::default()
+            .with_gears(vec!['*', '$'])
+            .with_gear_part_numbers(1..=2);
+        let engine =
+            Engine::try_build_with_rules(input.clone(), RaggedRows::Reject, rules).unwrap();
+        assert_eq!(get_sum_of_gear_ratios(engine), Ok(467835 + 617 + 664));
+
+        let rules = EngineRules::default().with_neighbourhood(Neighbourhood::VonNeumann(1));
+        let engine =
+            Engine::try_build_with_rules(input.clone(), RaggedRows::Reject, rules).unwrap();
+        assert_eq!(
+            get_sum_of_part_numbers(engine),
+            Ok(35 + 633 + 617 + 664 + 598)
+        );
+
+        // with another empty character, every '.' becomes a symbol
+        let rules = EngineRules::default().with_empty('#');
+        let engine = Engine::try_build_with_rules(input, RaggedRows::Reject, rules).unwrap();
+        assert_eq!(get_sum_of_part_numbers(engine), Ok(4361 + 114 + 58));
+    }
+
+    #[test]
+    fn test_input_queries() {
+        let engine = try_get_engine("../input/test_input.txt").unwrap();
+
+        let part_numbers = engine.get_part_numbers();
+        assert_eq!(part_numbers.len(), 10);
+        assert_eq!(part_numbers[1].get_value(), 114);
+        assert_eq!(part_numbers[1].get_row_index(), 0);
+        assert_eq!(part_numbers[1].get_column_indices(), 5..8);
+
+        let orphan_part_numbers: Vec<u64> = engine
+            .get_orphan_part_numbers()
+            .iter()
+            .map(|part_number| part_number.get_value())
+            .collect();
+        assert_eq!(orphan_part_numbers, vec![114, 58]);
+
+        let symbols = engine.get_symbols();
+        assert_eq!(symbols.len(), 6);
+        assert_eq!(symbols[0].get_value(), '*');
+        assert_eq!(symbols[0].get_position(), (1, 3));
+        let adjacent_part_numbers: Vec<u64> = symbols[0]
+            .get_adjacent_part_numbers()
+            .iter()
+            .map(|part_number| part_number.get_value())
+            .collect();
+        assert_eq!(adjacent_part_numbers, vec![467, 35]);
+
+        let touching_part_numbers: Vec<u64> = engine
+            .get_part_numbers_touching((1, 6))
+            .iter()
+            .map(|part_number| part_number.get_value())
+            .collect();
+        assert_eq!(touching_part_numbers, vec![114, 633]);
+        assert!(engine.get_part_numbers_touching((10, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_input_components() {
+        let engine = try_get_engine("../input/test_input.txt").unwrap();
+
+        let components = engine.get_components();
+        assert_eq!(components.len(), 8);
+
+        assert_eq!(components[0].get_part_number_ids(), &[0, 2]);
+        assert_eq!(components[0].get_symbol_positions(), &[(1, 3)]);
+        assert_eq!(components[0].get_size(), 3);
+        assert_eq!(components[0].get_sum(), Some(467 + 35));
+        assert_eq!(components[0].get_product(), Some(467 * 35));
+        assert!(components[0].get_symbols().contains(&'*'));
+
+        assert_eq!(components[1].get_size(), 1);
+        assert_eq!(components[1].get_sum(), Some(114));
+        assert!(components[1].get_symbols().is_empty());
+
+        assert_eq!(components[6].get_sum(), Some(755 + 598));
+
+        let dot = engine.to_dot();
+        assert!(dot.starts_with("graph schematic {\n"));
+        assert!(dot.contains("    n0 [shape=box, label=\"467 (0, 0..3)\"];\n"));
+        assert!(dot.contains("    s0 -- n0;\n    s0 -- n2;\n"));
+        assert_eq!(dot.matches(" -- ").count(), 8);
+    }
+
+    #[test]
+    fn test_render() {
+        let engine = Engine::try_build(vec!["12.3", "*..&"], RaggedRows::Reject).unwrap();
+
+        assert_eq!(
+            engine.render(RenderTarget::Ansi),
+            "\x1b[32m1\x1b[0m\x1b[32m2\x1b[0m.\x1b[32m3\x1b[0m\n\x1b[33m*\x1b[0m..\x1b[33m&\x1b[0m\n"
+        );
+
+        let html = engine.render(RenderTarget::Html);
+        assert!(
+            html.contains("<span class=\"part-number\">12</span><span class=\"empty\">.</span>")
+        );
+        assert!(html.contains("<span class=\"symbol\">&amp;</span>"));
+
+        let svg = engine.render(RenderTarget::Svg);
+        assert!(svg.starts_with("<svg "));
+        assert_eq!(svg.matches("<text ").count(), 5);
+
+        let engine = Engine::try_build(vec!["12.3", ".*..", "4..5"], RaggedRows::Reject).unwrap();
+        let html = engine.render(RenderTarget::Html);
+        assert!(html.contains("<span class=\"gear\">*</span>"));
+        assert!(html.contains("<span class=\"orphan-part-number\">5</span>"));
+
+        // punctuation that is no symbol shows up in every target
+        let rules = EngineRules::default().with_symbols(vec!['*']);
+        let engine = Engine::try_build_with_rules(vec!["1#2*"], RaggedRows::Reject, rules).unwrap();
+        assert_eq!(
+            engine.render(RenderTarget::Ansi),
+            "\x1b[2;31m1\x1b[0m#\x1b[32m2\x1b[0m\x1b[33m*\x1b[0m\n"
+        );
+        assert!(engine
+            .render(RenderTarget::Html)
+            .contains("<span class=\"inert\">#</span>"));
+        let svg = engine.render(RenderTarget::Svg);
+        assert_eq!(svg.matches("<text ").count(), 4);
+        assert!(svg.contains(">#</text>"));
+    }
+
+    #[test]
+    fn test_large_part_numbers() {
+        let engine =
+            Engine::try_build(vec!["12345678901.", "*........#9."], RaggedRows::Reject).unwrap();
+        assert_eq!(get_sum_of_part_numbers(engine), Ok(12345678901 + 9));
+
+        let engine = Engine::try_build(vec!["4294967296*4294967296"], RaggedRows::Reject).unwrap();
+        assert!(get_sum_of_gear_ratios(engine).is_err());
+
+        let engine = Engine::try_build(
+            vec!["18446744073709551615*18446744073709551615"],
+            RaggedRows::Reject,
+        )
+        .unwrap();
+        assert!(get_sum_of_part_numbers(engine).is_err());
+
+        assert!(Engine::try_build(vec!["18446744073709551616*"], RaggedRows::Reject).is_err());
+    }
+
+    #[test]
+    fn test_ragged_schematic() {
+        let input = vec!["467..114", "...*", "..35..633."];
+
+        assert!(Engine::try_build(input.clone(), RaggedRows::Reject).is_err());
+
+        let engine = Engine::try_build(input, RaggedRows::Pad).unwrap();
+        assert_eq!(get_sum_of_part_numbers(engine), Ok(467 + 35));
+    }
+
+    #[test]
+    fn test_empty_lines() {
+        let file_lines = get_schematic_lines("467..\n...*.\n..35.\n\n");
+        assert_eq!(file_lines, vec!["467..", "...*.", "..35."]);
+        assert_eq!(
+            Engine::try_build(file_lines, RaggedRows::Reject)
+                .unwrap()
+                .get_sum_of_part_numbers(),
+            Ok(467 + 35)
+        );
+
+        let file_lines = get_schematic_lines("467..\n...*.\n\n..35.\n");
+        assert_eq!(file_lines, vec!["467..", "...*.", "", "..35."]);
+        assert_eq!(
+            Engine::try_build(file_lines, RaggedRows::Reject).err(),
+            Some(String::from(
+                "Cannot build engine schematic, row 2 has 0 columns but expected 5"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_editing() {
+        let assert_matches_rebuild = |engine: &Engine| {
+            let rows = engine.get_rows();
+            let rebuilt_engine = Engine::try_build(
+                rows.iter().map(|row| row.as_str()).collect(),
+                RaggedRows::Reject,
+            )
+            .unwrap();
+
+            assert_eq!(engine.get_part_numbers(), rebuilt_engine.get_part_numbers());
+            assert_eq!(
+                engine.get_sum_of_part_numbers(),
+                rebuilt_engine.get_sum_of_part_numbers()
+            );
+            assert_eq!(engine.get_gear_ratios(), rebuilt_engine.get_gear_ratios());
+            assert_eq!(
+                engine.get_sum_of_gear_ratios(),
+                rebuilt_engine.get_sum_of_gear_ratios()
+            );
+        };
+
+        let mut engine = try_get_engine("../input/test_input.txt").unwrap();
+
+        // removing the symbol orphans 467 and 35 and the gear goes away
+        engine.try_set_cell((1, 3), '.').unwrap();
+        assert_eq!(engine.get_sum_of_part_numbers(), Ok(4361 - 467 - 35));
+        assert_eq!(engine.get_sum_of_gear_ratios(), Ok(467835 - 16345));
+        assert_matches_rebuild(&engine);
+
+        // joining 467 and 114 into one part number next to a new gear
+        engine.try_set_cell((0, 3), '0').unwrap();
+        engine.try_set_cell((0, 4), '0').unwrap();
+        engine.try_set_cell((1, 0), '*').unwrap();
+        assert_matches_rebuild(&engine);
+
+        engine.try_set_cell((0, 3), '.').unwrap();
+        assert_matches_rebuild(&engine);
+
+        engine.try_insert_row(3).unwrap();
+        assert_matches_rebuild(&engine);
+        engine.try_set_cell((3, 3), '*').unwrap();
+        assert_matches_rebuild(&engine);
+
+        engine.try_delete_row(0).unwrap();
+        assert_matches_rebuild(&engine);
+        engine.try_delete_row(engine.get_rows().len() - 1).unwrap();
+        assert_matches_rebuild(&engine);
+
+        engine.try_insert_column(0).unwrap();
+        assert_matches_rebuild(&engine);
+        engine.try_delete_column(4).unwrap();
+        assert_matches_rebuild(&engine);
+
+        assert!(engine.try_set_cell((100, 0), '.').is_err());
+        assert!(engine.try_set_cell((0, 0), 'a').is_err());
+        assert!(engine.try_delete_row(100).is_err());
+        assert_matches_rebuild(&engine);
+
+        // edits that would create a part number beyond u64 leave the engine unchanged
+        let mut engine = Engine::try_build(
+            vec!["1844674407370955161.6", "....................*"],
+            RaggedRows::Reject,
+        )
+        .unwrap();
+        assert!(engine.try_set_cell((0, 19), '1').is_err());
+        assert!(engine.try_delete_column(19).is_err());
+        assert_eq!(engine.get_sum_of_part_numbers(), Ok(6));
+        assert_matches_rebuild(&engine);
+
+        // splitting or joining a part number changes gears outside the band around the column
+        let input = vec!["12345.", "*....*", "8...67"];
+        for column_index in 0..=input[0].len() {
+            let mut engine = Engine::try_build(input.clone(), RaggedRows::Reject).unwrap();
+            engine.try_insert_column(column_index).unwrap();
+            assert_matches_rebuild(&engine);
+        }
+        for column_index in 0..input[0].len() {
+            let mut engine = Engine::try_build(input.clone(), RaggedRows::Reject).unwrap();
+            engine.try_delete_column(column_index).unwrap();
+            assert_matches_rebuild(&engine);
+        }
+        let mut engine = Engine::try_build(input.clone(), RaggedRows::Reject).unwrap();
+        engine.try_insert_column(3).unwrap();
+        assert_eq!(engine.get_sum_of_gear_ratios(), Ok(123 * 8 + 45 * 67));
+        engine.try_delete_column(3).unwrap();
+        assert_eq!(engine.get_sum_of_gear_ratios(), Ok(12345 * 8 + 12345 * 67));
+        assert_matches_rebuild(&engine);
+
+        // deleting the only column keeps the rows, so a column can be inserted again
+        let mut engine = Engine::try_build(vec!["1", "."], RaggedRows::Reject).unwrap();
+        engine.try_delete_column(0).unwrap();
+        assert_eq!(engine.get_rows(), vec!["", ""]);
+        engine.try_insert_column(0).unwrap();
+        assert_eq!(engine.get_rows(), vec![".", "."]);
+        engine.try_set_cell((0, 0), '7').unwrap();
+        engine.try_insert_column(1).unwrap();
+        engine.try_set_cell((1, 1), '*').unwrap();
+        assert_eq!(engine.get_sum_of_part_numbers(), Ok(7));
+        assert_matches_rebuild(&engine);
+    }
 }
@@ -1,7 +1,26 @@
-use advent_of_code_2023_day_03::{part_1, part_2};
+use advent_of_code_2023_day_03::{part_1, part_2, render, to_dot, RenderTarget};
+use std::env;
 
 fn main() {
-    println!("Result of part 1: {}", part_1("../input/input.txt"));
+    let args: Vec<String> = env::args().collect();
 
-    println!("Result of part 2: {}", part_2("../input/input.txt"));
+    match (args.get(1).map(|arg| arg.as_str()), args.get(2)) {
+        (Some("--render"), Some(target)) => match RenderTarget::try_from(target.as_str()) {
+            Ok(target) => match render("../input/input.txt", target) {
+                Ok(output) => print!("{}", output),
+                Err(render_error) => println!("Cannot render engine, {}", render_error),
+            },
+            Err(target_error) => println!("{}", target_error),
+        },
+        (Some("--dot"), None) => match to_dot("../input/input.txt") {
+            Ok(output) => print!("{}", output),
+            Err(dot_error) => println!("Cannot export engine graph, {}", dot_error),
+        },
+        (None, _) => {
+            println!("Result of part 1: {}", part_1("../input/input.txt"));
+
+            println!("Result of part 2: {}", part_2("../input/input.txt"));
+        }
+        _ => println!("Usage: {} [--render <ansi|html|svg> | --dot]", args[0]),
+    }
 }
@@ -0,0 +1,185 @@
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CellKind {
+    Empty,
+    // punctuation that is no symbol under the rules, shown but never counted
+    Inert,
+    PartNumber,
+    OrphanPartNumber,
+    Symbol,
+    Gear,
+}
+
+impl CellKind {
+    fn get_ansi_code(&self) -> Option<&'static str> {
+        match self {
+            CellKind::Empty | CellKind::Inert => None,
+            CellKind::PartNumber => Some("\x1b[32m"),
+            CellKind::OrphanPartNumber => Some("\x1b[2;31m"),
+            CellKind::Symbol => Some("\x1b[33m"),
+            CellKind::Gear => Some("\x1b[1;35m"),
+        }
+    }
+
+    fn get_css_class(&self) -> &'static str {
+        match self {
+            CellKind::Empty => "empty",
+            CellKind::Inert => "inert",
+            CellKind::PartNumber => "part-number",
+            CellKind::OrphanPartNumber => "orphan-part-number",
+            CellKind::Symbol => "symbol",
+            CellKind::Gear => "gear",
+        }
+    }
+
+    fn get_colour(&self) -> &'static str {
+        match self {
+            CellKind::Empty | CellKind::Inert => "#9e9e9e",
+            CellKind::PartNumber => "#2e7d32",
+            CellKind::OrphanPartNumber => "#c62828",
+            CellKind::Symbol => "#f9a825",
+            CellKind::Gear => "#8e24aa",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RenderTarget {
+    Ansi,
+    Html,
+    Svg,
+}
+
+impl TryFrom<&str> for RenderTarget {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "ansi" => Ok(RenderTarget::Ansi),
+            "html" => Ok(RenderTarget::Html),
+            "svg" => Ok(RenderTarget::Svg),
+            _ => Err(format!("Cannot parse RenderTarget from {}", value)),
+        }
+    }
+}
+
+const SVG_CELL_WIDTH: usize = 10;
+const SVG_CELL_HEIGHT: usize = 16;
+
+pub fn render(cells: &[Vec<(char, CellKind)>], target: RenderTarget) -> String {
+    match target {
+        RenderTarget::Ansi => render_ansi(cells),
+        RenderTarget::Html => render_html(cells),
+        RenderTarget::Svg => render_svg(cells),
+    }
+}
+
+fn render_ansi(cells: &[Vec<(char, CellKind)>]) -> String {
+    let mut output = String::new();
+
+    for row in cells {
+        for (character, cell_kind) in row {
+            match cell_kind.get_ansi_code() {
+                Some(ansi_code) => {
+                    output.push_str(ansi_code);
+                    output.push(*character);
+                    output.push_str("\x1b[0m");
+                }
+                None => output.push(*character),
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+fn render_html(cells: &[Vec<(char, CellKind)>]) -> String {
+    let mut output = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Engine schematic</title>\n<style>\n",
+    );
+
+    output.push_str("pre { font-family: monospace; line-height: 1.2; }\n");
+    for cell_kind in [
+        CellKind::Empty,
+        CellKind::Inert,
+        CellKind::PartNumber,
+        CellKind::OrphanPartNumber,
+        CellKind::Symbol,
+        CellKind::Gear,
+    ] {
+        output.push_str(&format!(
+            ".{} {{ color: {}; }}\n",
+            cell_kind.get_css_class(),
+            cell_kind.get_colour()
+        ));
+    }
+    output.push_str(".gear { font-weight: bold; }\n</style>\n</head>\n<body>\n<pre>\n");
+
+    for row in cells {
+        let mut current_cell_kind = None;
+
+        for (character, cell_kind) in row {
+            // consecutive cells of the same kind share one span
+            if current_cell_kind != Some(*cell_kind) {
+                if current_cell_kind.is_some() {
+                    output.push_str("</span>");
+                }
+                output.push_str(&format!("<span class=\"{}\">", cell_kind.get_css_class()));
+                current_cell_kind = Some(*cell_kind);
+            }
+
+            output.push_str(&escape(*character));
+        }
+
+        if current_cell_kind.is_some() {
+            output.push_str("</span>");
+        }
+        output.push('\n');
+    }
+
+    output.push_str("</pre>\n</body>\n</html>\n");
+
+    output
+}
+
+fn render_svg(cells: &[Vec<(char, CellKind)>]) -> String {
+    let number_of_columns = cells.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    let mut output = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"14\">\n",
+        number_of_columns * SVG_CELL_WIDTH,
+        cells.len() * SVG_CELL_HEIGHT
+    );
+    output.push_str("<rect width=\"100%\" height=\"100%\" fill=\"#ffffff\"/>\n");
+
+    for (row_index, row) in cells.iter().enumerate() {
+        for (column_index, (character, cell_kind)) in row.iter().enumerate() {
+            if *cell_kind == CellKind::Empty {
+                continue;
+            }
+
+            output.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>\n",
+                column_index * SVG_CELL_WIDTH,
+                (row_index + 1) * SVG_CELL_HEIGHT - 4,
+                cell_kind.get_colour(),
+                escape(*character)
+            ));
+        }
+    }
+
+    output.push_str("</svg>\n");
+
+    output
+}
+
+fn escape(character: char) -> String {
+    match character {
+        '&' => String::from("&amp;"),
+        '<' => String::from("&lt;"),
+        '>' => String::from("&gt;"),
+        '"' => String::from("&quot;"),
+        '\'' => String::from("&#39;"),
+        c => c.to_string(),
+    }
+}
@@ -0,0 +1,113 @@
+use std::ops::RangeInclusive;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Neighbourhood {
+    // cells within the given manhattan distance, a radius of 1 gives the 4 direct neighbours
+    VonNeumann(usize),
+    // cells within the given chebyshev distance, a radius of 1 gives all 8 surrounding cells
+    Moore(usize),
+}
+
+impl Neighbourhood {
+    pub fn get_radius(&self) -> usize {
+        match self {
+            Neighbourhood::VonNeumann(radius) | Neighbourhood::Moore(radius) => *radius,
+        }
+    }
+
+    pub fn contains_offset(&self, row_offset: i64, column_offset: i64) -> bool {
+        if row_offset == 0 && column_offset == 0 {
+            return false;
+        }
+
+        match self {
+            Neighbourhood::VonNeumann(radius) => {
+                row_offset.unsigned_abs() + column_offset.unsigned_abs() <= *radius as u64
+            }
+            Neighbourhood::Moore(radius) => {
+                row_offset.unsigned_abs() <= *radius as u64
+                    && column_offset.unsigned_abs() <= *radius as u64
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EngineRules {
+    empty: char,
+    // when no symbols are given, any ascii punctuation is a symbol
+    symbols: Option<Vec<char>>,
+    gears: Vec<char>,
+    gear_part_numbers: RangeInclusive<usize>,
+    neighbourhood: Neighbourhood,
+}
+
+impl Default for EngineRules {
+    fn default() -> Self {
+        EngineRules {
+            empty: '.',
+            symbols: None,
+            gears: vec!['*'],
+            gear_part_numbers: 2..=2,
+            neighbourhood: Neighbourhood::Moore(1),
+        }
+    }
+}
+
+impl EngineRules {
+    pub fn with_empty(mut self, empty: char) -> Self {
+        self.empty = empty;
+        self
+    }
+
+    pub fn with_symbols(mut self, symbols: Vec<char>) -> Self {
+        self.symbols = Some(symbols);
+        self
+    }
+
+    pub fn with_gears(mut self, gears: Vec<char>) -> Self {
+        self.gears = gears;
+        self
+    }
+
+    pub fn with_gear_part_numbers(mut self, gear_part_numbers: RangeInclusive<usize>) -> Self {
+        self.gear_part_numbers = gear_part_numbers;
+        self
+    }
+
+    pub fn with_neighbourhood(mut self, neighbourhood: Neighbourhood) -> Self {
+        self.neighbourhood = neighbourhood;
+        self
+    }
+
+    pub fn get_empty(&self) -> char {
+        self.empty
+    }
+
+    pub fn is_empty(&self, character: char) -> bool {
+        character == self.empty
+    }
+
+    pub fn is_symbol(&self, character: char) -> bool {
+        if character == self.empty || character.is_ascii_digit() {
+            return false;
+        }
+
+        match &self.symbols {
+            Some(symbols) => symbols.contains(&character),
+            None => character.is_ascii_punctuation(),
+        }
+    }
+
+    pub fn is_gear(&self, character: char) -> bool {
+        self.gears.contains(&character)
+    }
+
+    pub fn is_gear_part_number_count(&self, number_of_part_numbers: usize) -> bool {
+        self.gear_part_numbers.contains(&number_of_part_numbers)
+    }
+
+    pub fn get_neighbourhood(&self) -> Neighbourhood {
+        self.neighbourhood
+    }
+}
@@ -1,124 +1,249 @@
+use std::cmp::Ordering;
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum DuplicateNumbers {
+    // duplicates are counted once and can be listed afterwards
+    #[default]
+    Report,
+    Reject,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum CardSequence {
+    // card ids start at 1 and follow each other without gaps
+    #[default]
+    Contiguous,
+    // any set of distinct card ids in any order, as in a partially scanned pile
+    Sparse,
+}
+
 #[derive(Debug)]
 pub struct Card {
     id: u32,
+    // both lists are sorted and free of duplicates so that matching is a single merge
     winning_numbers: Vec<u32>,
     own_numbers: Vec<u32>,
+    matching_numbers: Vec<u32>,
+    duplicate_winning_numbers: Vec<u32>,
+    duplicate_own_numbers: Vec<u32>,
 }
 
 impl Card {
-    pub fn try_build(input: &str) -> Option<Self> {
-        let id = Card::try_parse_id(input);
-        let winning_numbers = Card::try_parse_winning_numbers(input);
-        let own_numbers = Card::try_parse_own_numbers(input);
-
-        if let (Some(id), Some(winning_numbers), Some(own_numbers)) =
-            (id, winning_numbers, own_numbers)
-        {
-            Some(Card {
-                id,
-                winning_numbers,
-                own_numbers,
-            })
-        } else {
-            None
-        }
+    pub fn try_build(input: &str) -> Result<Self, String> {
+        Self::try_build_with_duplicate_numbers(input, DuplicateNumbers::default())
     }
 
-    fn try_parse_id(input: &str) -> Option<u32> {
-        let splitted_input: Vec<&str> = input.split(':').collect();
-
-        if let Some(card_input) = splitted_input.first() {
-            let splitted_card_input: Vec<&str> = card_input.split_whitespace().collect();
-            if let Some(card_number) = splitted_card_input.last() {
-                match card_number.parse::<u32>() {
-                    Ok(card_number) => Some(card_number),
-                    Err(card_number_parsing_error) => {
-                        println!("Could not parse card number, {}", card_number_parsing_error);
-                        None
-                    }
-                }
-            } else {
-                println!("No card number found");
-                None
+    pub fn try_build_with_duplicate_numbers(
+        input: &str,
+        duplicate_numbers: DuplicateNumbers,
+    ) -> Result<Self, String> {
+        let (card_input, numbers_input) = input
+            .split_once(':')
+            .ok_or_else(|| format!("Cannot find numbers input in \"{}\"", input))?;
+
+        let id = Self::try_parse_id(card_input)?;
+
+        let (winning_numbers_input, own_numbers_input) = numbers_input
+            .split_once('|')
+            .ok_or_else(|| format!("Cannot find own numbers input of card {}", id))?;
+
+        let (winning_numbers, duplicate_winning_numbers) =
+            Self::try_parse_numbers(winning_numbers_input)
+                .map_err(|numbers_error| format!("Card {} {}", id, numbers_error))?;
+        let (own_numbers, duplicate_own_numbers) = Self::try_parse_numbers(own_numbers_input)
+            .map_err(|numbers_error| format!("Card {} {}", id, numbers_error))?;
+
+        if duplicate_numbers == DuplicateNumbers::Reject {
+            if !duplicate_winning_numbers.is_empty() {
+                return Err(format!(
+                    "Card {} has duplicate winning numbers {:?}",
+                    id, duplicate_winning_numbers
+                ));
+            }
+
+            if !duplicate_own_numbers.is_empty() {
+                return Err(format!(
+                    "Card {} has duplicate own numbers {:?}",
+                    id, duplicate_own_numbers
+                ));
             }
-        } else {
-            println!("No card input found");
-            None
         }
-    }
 
-    fn try_parse_winning_numbers(input: &str) -> Option<Vec<u32>> {
-        let splitted_input: Vec<&str> = input.split(':').collect();
-
-        if let Some(numbers_input) = splitted_input.last() {
-            let splitted_numbers_input: Vec<&str> = numbers_input.split('|').collect();
-            if let Some(winning_numbers_input) = splitted_numbers_input.first() {
-                let winning_numbers_input: Vec<&str> =
-                    winning_numbers_input.split_whitespace().collect();
-
-                let mut winning_numbers = Vec::new();
-                for winning_number in winning_numbers_input {
-                    match winning_number.parse::<u32>() {
-                        Ok(winning_number) => winning_numbers.push(winning_number),
-                        Err(winning_number_parsing_error) => {
-                            println!(
-                                "Could not parse winning number, {}",
-                                winning_number_parsing_error
-                            );
-                            return None;
-                        }
-                    }
-                }
+        let matching_numbers = get_intersection(&winning_numbers, &own_numbers);
 
-                Some(winning_numbers)
-            } else {
-                println!("No winning numbers input found");
-                None
-            }
-        } else {
-            println!("No numbers input found");
-            None
+        Ok(Card {
+            id,
+            winning_numbers,
+            own_numbers,
+            matching_numbers,
+            duplicate_winning_numbers,
+            duplicate_own_numbers,
+        })
+    }
+
+    fn try_parse_id(input: &str) -> Result<u32, String> {
+        match input.trim().strip_prefix("Card") {
+            Some(card_id) => card_id.trim().parse::<u32>().map_err(|card_id_error| {
+                format!(
+                    "Cannot parse card id \"{}\", {}",
+                    card_id.trim(),
+                    card_id_error
+                )
+            }),
+            None => Err(format!("No card id found in \"{}\"", input)),
         }
     }
 
-    fn try_parse_own_numbers(input: &str) -> Option<Vec<u32>> {
-        let splitted_input: Vec<&str> = input.split(':').collect();
-
-        if let Some(numbers_input) = splitted_input.last() {
-            let splitted_numbers_input: Vec<&str> = numbers_input.split('|').collect();
-            if let Some(own_numbers_input) = splitted_numbers_input.last() {
-                let own_numbers_input: Vec<&str> = own_numbers_input.split_whitespace().collect();
-
-                let mut own_numbers = Vec::new();
-                for own_number in own_numbers_input {
-                    match own_number.parse::<u32>() {
-                        Ok(own_number) => own_numbers.push(own_number),
-                        Err(own_number_parsing_error) => {
-                            println!("Could not parse own number, {}", own_number_parsing_error);
-                            return None;
-                        }
-                    }
+    // returns the sorted numbers without duplicates and the numbers that were given more than once
+    fn try_parse_numbers(input: &str) -> Result<(Vec<u32>, Vec<u32>), String> {
+        let mut numbers = Vec::new();
+
+        for number in input.split_whitespace() {
+            match number.parse::<u32>() {
+                Ok(number) => numbers.push(number),
+                Err(number_error) => {
+                    return Err(format!(
+                        "has invalid number \"{}\", {}",
+                        number, number_error
+                    ))
                 }
+            }
+        }
+
+        numbers.sort_unstable();
 
-                Some(own_numbers)
-            } else {
-                println!("No won numbers input found");
-                None
+        let mut duplicate_numbers: Vec<u32> = Vec::new();
+        for pair in numbers.windows(2) {
+            if pair[0] == pair[1] && duplicate_numbers.last() != Some(&pair[0]) {
+                duplicate_numbers.push(pair[0]);
             }
-        } else {
-            println!("No numbers input found");
-            None
         }
+
+        numbers.dedup();
+
+        Ok((numbers, duplicate_numbers))
     }
 
     pub fn get_id(&self) -> u32 {
         self.id
     }
 
+    pub fn get_winning_numbers(&self) -> &[u32] {
+        &self.winning_numbers
+    }
+
+    pub fn get_own_numbers(&self) -> &[u32] {
+        &self.own_numbers
+    }
+
+    pub fn get_matching_numbers(&self) -> &[u32] {
+        &self.matching_numbers
+    }
+
     pub fn get_number_of_own_winning_numbers(&self) -> u32 {
-        self.own_numbers
-            .iter()
-            .filter(|number| self.winning_numbers.contains(number))
-            .count() as u32
+        self.matching_numbers.len() as u32
+    }
+
+    pub fn get_duplicate_winning_numbers(&self) -> &[u32] {
+        &self.duplicate_winning_numbers
+    }
+
+    pub fn get_duplicate_own_numbers(&self) -> &[u32] {
+        &self.duplicate_own_numbers
+    }
+}
+
+fn get_intersection(first_numbers: &[u32], second_numbers: &[u32]) -> Vec<u32> {
+    let mut intersection = Vec::new();
+    let (mut first_index, mut second_index) = (0, 0);
+
+    while let (Some(first_number), Some(second_number)) = (
+        first_numbers.get(first_index),
+        second_numbers.get(second_index),
+    ) {
+        match first_number.cmp(second_number) {
+            Ordering::Less => first_index += 1,
+            Ordering::Greater => second_index += 1,
+            Ordering::Equal => {
+                intersection.push(*first_number);
+                first_index += 1;
+                second_index += 1;
+            }
+        }
+    }
+
+    intersection
+}
+
+pub fn validate_card_ids(cards: &[Card], card_sequence: CardSequence) -> Result<(), String> {
+    match card_sequence {
+        CardSequence::Contiguous => {
+            if cards.iter().any(|card| card.get_id() == 0) {
+                return Err(String::from("Card id 0 is invalid, card ids start at 1"));
+            }
+
+            // the ids are checked as a set first, so a card that is only misplaced is not
+            // reported as missing
+            let mut card_ids: Vec<u32> = cards.iter().map(|card| card.get_id()).collect();
+            card_ids.sort_unstable();
+
+            if let Some(pair) = card_ids.windows(2).find(|pair| pair[0] == pair[1]) {
+                return Err(format!("Card {} appears more than once", pair[0]));
+            }
+
+            match card_ids.first() {
+                Some(2) => {
+                    return Err(String::from("Card 1 is missing, the lowest card is card 2"))
+                }
+                Some(card_id) if *card_id > 2 => {
+                    return Err(format!(
+                        "Cards 1 to {} are missing, the lowest card is card {}",
+                        card_id - 1,
+                        card_id
+                    ))
+                }
+                _ => (),
+            }
+
+            for pair in card_ids.windows(2) {
+                if pair[1] == pair[0] + 2 {
+                    return Err(format!(
+                        "Card {} is missing between card {} and card {}",
+                        pair[0] + 1,
+                        pair[0],
+                        pair[1]
+                    ));
+                } else if pair[1] > pair[0] + 2 {
+                    return Err(format!(
+                        "Cards {} to {} are missing between card {} and card {}",
+                        pair[0] + 1,
+                        pair[1] - 1,
+                        pair[0],
+                        pair[1]
+                    ));
+                }
+            }
+
+            match cards
+                .windows(2)
+                .find(|pair| pair[0].get_id() > pair[1].get_id())
+            {
+                Some(pair) => Err(format!(
+                    "Card {} is out of order, it comes before card {}",
+                    pair[0].get_id(),
+                    pair[1].get_id()
+                )),
+                None => Ok(()),
+            }
+        }
+        CardSequence::Sparse => {
+            let mut card_ids: Vec<u32> = cards.iter().map(|card| card.get_id()).collect();
+            card_ids.sort_unstable();
+
+            match card_ids.windows(2).find(|pair| pair[0] == pair[1]) {
+                Some(pair) => Err(format!("Card {} appears more than once", pair[0])),
+                None => Ok(()),
+            }
+        }
     }
 }
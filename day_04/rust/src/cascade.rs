@@ -0,0 +1,306 @@
+use super::card::Card;
+use super::graph;
+use std::ops::Range;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Direction {
+    // winning cards win copies of the cards after them, the pile is played front to back
+    Following,
+    // winning cards win copies of the cards before them, the pile is played back to front
+    Preceding,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Reach {
+    // a card wins copies of as many cards as it has matching numbers
+    Matches,
+    // a card with at least one matching number wins copies of a fixed number of cards
+    Fixed(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct CascadeRules {
+    direction: Direction,
+    reach: Reach,
+    copies_per_card: u64,
+    // copies reaching past the end of the pile continue at its start, those cards have already
+    // been played so the copies are counted but do not win any further copies
+    wrap_around: bool,
+}
+
+impl Default for CascadeRules {
+    fn default() -> Self {
+        CascadeRules {
+            direction: Direction::Following,
+            reach: Reach::Matches,
+            copies_per_card: 1,
+            wrap_around: false,
+        }
+    }
+}
+
+impl CascadeRules {
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn with_reach(mut self, reach: Reach) -> Self {
+        self.reach = reach;
+        self
+    }
+
+    pub fn with_copies_per_card(mut self, copies_per_card: u64) -> Self {
+        self.copies_per_card = copies_per_card;
+        self
+    }
+
+    pub fn with_wrap_around(mut self, wrap_around: bool) -> Self {
+        self.wrap_around = wrap_around;
+        self
+    }
+
+    pub fn get_direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn get_reach(&self, number_of_matches: u32) -> usize {
+        match self.reach {
+            Reach::Matches => number_of_matches as usize,
+            Reach::Fixed(_) if number_of_matches == 0 => 0,
+            Reach::Fixed(reach) => reach,
+        }
+    }
+
+    pub fn get_copies_per_card(&self) -> u64 {
+        self.copies_per_card
+    }
+
+    pub fn is_wrap_around(&self) -> bool {
+        self.wrap_around
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Cascade {
+    // sorted by card id
+    card_ids: Vec<u32>,
+    number_of_copies: Vec<u64>,
+    // the card indices of each winning card and the cards it won, with the copies of each
+    won_card_ranges: Vec<(usize, Range<usize>, u64)>,
+    total: u64,
+}
+
+impl Cascade {
+    // cards may come in any order and with gaps, copies are only won of cards that are present
+    pub fn try_build(cards: &[Card], rules: &CascadeRules) -> Result<Self, String> {
+        let number_of_cards = cards.len();
+
+        let mut played_cards: Vec<&Card> = cards.iter().collect();
+        played_cards.sort_by_key(|card| card.get_id());
+
+        if let Some(pair) = played_cards
+            .windows(2)
+            .find(|pair| pair[0].get_id() == pair[1].get_id())
+        {
+            return Err(format!(
+                "Cannot build cascade, card {} appears more than once",
+                pair[0].get_id()
+            ));
+        }
+
+        if rules.get_direction() == Direction::Preceding {
+            played_cards.reverse();
+        }
+
+        // the distance of each card id to the first played card id, so the cards won by a
+        // card are found by their distance no matter which direction the pile is played in
+        let distances: Vec<usize> = played_cards
+            .iter()
+            .map(|card| card.get_id().abs_diff(played_cards[0].get_id()) as usize)
+            .collect();
+        let span = distances.last().map_or(0, |distance| distance + 1);
+
+        let mut won_copies_start = vec![0u64; number_of_cards + 1];
+        let mut won_copies_end = vec![0u64; number_of_cards + 1];
+        let mut late_copies_start = vec![0u64; number_of_cards + 1];
+        let mut late_copies_end = vec![0u64; number_of_cards + 1];
+
+        let mut number_of_copies = Vec::with_capacity(number_of_cards);
+        let mut won_card_ranges = Vec::new();
+        let mut won_copies: u64 = 0;
+
+        for (position, card) in played_cards.iter().enumerate() {
+            let overflow_error = || {
+                format!(
+                    "Number of copies of card {} does not fit into u64",
+                    card.get_id()
+                )
+            };
+
+            won_copies = won_copies
+                .checked_add(won_copies_start[position])
+                .ok_or_else(overflow_error)?
+                - won_copies_end[position];
+
+            let card_copies = won_copies.checked_add(1).ok_or_else(overflow_error)?;
+            number_of_copies.push(card_copies);
+
+            // a card never wins copies of itself
+            let reach = rules.get_reach(card.get_number_of_own_winning_numbers());
+            let reach = if rules.is_wrap_around() {
+                reach.min(span - 1)
+            } else {
+                reach
+            };
+
+            if reach == 0 {
+                continue;
+            }
+
+            let copies = card_copies
+                .checked_mul(rules.get_copies_per_card())
+                .ok_or_else(overflow_error)?;
+            let end_distance = distances[position].saturating_add(reach + 1);
+            let end = distances.partition_point(|distance| *distance < end_distance);
+
+            if end > position + 1 {
+                won_copies_start[position + 1] = won_copies_start[position + 1]
+                    .checked_add(copies)
+                    .ok_or_else(overflow_error)?;
+                won_copies_end[end] = won_copies_end[end]
+                    .checked_add(copies)
+                    .ok_or_else(overflow_error)?;
+                won_card_ranges.push((position, position + 1..end, copies));
+            }
+
+            if rules.is_wrap_around() && end_distance > span {
+                let late_end =
+                    distances.partition_point(|distance| *distance < end_distance - span);
+
+                if late_end > 0 {
+                    late_copies_start[0] = late_copies_start[0]
+                        .checked_add(copies)
+                        .ok_or_else(overflow_error)?;
+                    late_copies_end[late_end] = late_copies_end[late_end]
+                        .checked_add(copies)
+                        .ok_or_else(overflow_error)?;
+                    won_card_ranges.push((position, 0..late_end, copies));
+                }
+            }
+        }
+
+        let mut late_copies: u64 = 0;
+
+        for (position, card) in played_cards.iter().enumerate() {
+            let overflow_error = || {
+                format!(
+                    "Number of copies of card {} does not fit into u64",
+                    card.get_id()
+                )
+            };
+
+            late_copies = late_copies
+                .checked_add(late_copies_start[position])
+                .ok_or_else(overflow_error)?
+                - late_copies_end[position];
+
+            number_of_copies[position] = number_of_copies[position]
+                .checked_add(late_copies)
+                .ok_or_else(overflow_error)?;
+        }
+
+        if rules.get_direction() == Direction::Preceding {
+            played_cards.reverse();
+            number_of_copies.reverse();
+
+            for (position, won_positions, _) in &mut won_card_ranges {
+                *position = number_of_cards - 1 - *position;
+                *won_positions =
+                    number_of_cards - won_positions.end..number_of_cards - won_positions.start;
+            }
+        }
+
+        let total = number_of_copies
+            .iter()
+            .try_fold(0u64, |total, card_copies| total.checked_add(*card_copies))
+            .ok_or_else(|| String::from("Total number of scratchcards does not fit into u64"))?;
+
+        Ok(Cascade {
+            card_ids: played_cards.iter().map(|card| card.get_id()).collect(),
+            number_of_copies,
+            won_card_ranges,
+            total,
+        })
+    }
+
+    pub fn get_number_of_copies(&self, card_id: u32) -> Option<u64> {
+        self.card_ids
+            .binary_search(&card_id)
+            .ok()
+            .map(|card_index| self.number_of_copies[card_index])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, u64)> + '_ {
+        self.card_ids
+            .iter()
+            .copied()
+            .zip(self.number_of_copies.iter().copied())
+    }
+
+    pub fn get_number_of_won_copies(&self, card_id: u32) -> Option<u64> {
+        self.get_number_of_copies(card_id)
+            .map(|number_of_copies| number_of_copies - 1)
+    }
+
+    pub fn get_wins(&self) -> Vec<Win> {
+        let mut wins = Vec::new();
+
+        for (card_index, won_card_indices, number_of_copies) in &self.won_card_ranges {
+            for won_card_index in won_card_indices.clone() {
+                wins.push(Win {
+                    card_id: self.card_ids[*card_index],
+                    won_card_id: self.card_ids[won_card_index],
+                    number_of_copies: *number_of_copies,
+                });
+            }
+        }
+
+        wins.sort_by_key(|win| (win.card_id, win.won_card_id));
+
+        wins
+    }
+
+    pub fn get_total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn to_dot(&self) -> String {
+        graph::to_dot(self)
+    }
+
+    pub fn to_table(&self) -> String {
+        graph::to_table(self)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Win {
+    card_id: u32,
+    won_card_id: u32,
+    number_of_copies: u64,
+}
+
+impl Win {
+    pub fn get_card_id(&self) -> u32 {
+        self.card_id
+    }
+
+    pub fn get_won_card_id(&self) -> u32 {
+        self.won_card_id
+    }
+
+    pub fn get_number_of_copies(&self) -> u64 {
+        self.number_of_copies
+    }
+}
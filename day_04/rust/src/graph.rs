@@ -0,0 +1,65 @@
+use super::cascade::Cascade;
+
+pub fn to_dot(cascade: &Cascade) -> String {
+    let mut output = String::from("digraph cascade {\n");
+
+    for (card_id, number_of_copies) in cascade.iter() {
+        output.push_str(&format!(
+            "    c{} [shape=box, label=\"Card {}\\n1 original, {} won\"];\n",
+            card_id,
+            card_id,
+            number_of_copies - 1
+        ));
+    }
+
+    for win in cascade.get_wins() {
+        output.push_str(&format!(
+            "    c{} -> c{} [label=\"{}\"];\n",
+            win.get_card_id(),
+            win.get_won_card_id(),
+            win.get_number_of_copies()
+        ));
+    }
+
+    output.push_str("}\n");
+
+    output
+}
+
+pub fn to_table(cascade: &Cascade) -> String {
+    let header = ["Card", "Original", "Won", "Total"];
+
+    let rows: Vec<[String; 4]> = cascade
+        .iter()
+        .map(|(card_id, number_of_copies)| {
+            [
+                card_id.to_string(),
+                String::from("1"),
+                (number_of_copies - 1).to_string(),
+                number_of_copies.to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(|column| column.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut output = String::new();
+
+    for row in std::iter::once(header.map(String::from)).chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+            .collect();
+
+        output.push_str(&cells.join("  "));
+        output.push('\n');
+    }
+
+    output
+}
@@ -1,76 +1,124 @@
 mod card;
+mod cascade;
+mod graph;
+mod scoring;
 
-use card::Card;
-use std::{collections::HashMap, fs};
+use std::fs;
 
-pub fn part_1(file_name: &str) -> u32 {
-    let cards = try_get_cards(file_name);
+pub use card::{validate_card_ids, Card, CardSequence, DuplicateNumbers};
+pub use cascade::{Cascade, CascadeRules, Direction, Reach, Win};
+pub use scoring::{try_parse_scoring_rule, Doubling, Fibonacci, Linear, ScoringRule, Table};
 
-    get_points(cards)
+pub fn part_1(file_name: &str) -> u64 {
+    part_1_with_scoring_rule(file_name, &Doubling)
 }
 
-pub fn part_2(file_name: &str) -> u32 {
-    let cards = try_get_cards(file_name);
+pub fn part_1_with_scoring_rule(file_name: &str, scoring_rule: &dyn ScoringRule) -> u64 {
+    let cards = try_get_cards(
+        file_name,
+        CardSequence::Contiguous,
+        DuplicateNumbers::Report,
+    );
 
-    get_number_of_scratchcards(cards)
+    match cards.and_then(|cards| get_points(cards, scoring_rule)) {
+        Ok(points) => points,
+        Err(points_error) => {
+            println!("Failed to get points, {}", points_error);
+            0
+        }
+    }
 }
 
-fn try_get_cards(file_name: &str) -> Vec<Card> {
-    let file_content =
-        fs::read_to_string(file_name).expect("input file should be located in input folder");
-    let file_lines: Vec<&str> = file_content
-        .split('\n')
-        .map(|file_line| file_line.trim())
-        .collect();
-
-    let mut cards = Vec::new();
-    for line in file_lines {
-        if let Some(card) = Card::try_build(line) {
-            cards.push(card);
+pub fn part_2(file_name: &str) -> u64 {
+    match get_cascade(
+        file_name,
+        CardSequence::Contiguous,
+        &CascadeRules::default(),
+    ) {
+        Ok(cascade) => cascade.get_total(),
+        Err(cascade_error) => {
+            println!("Failed to get cascade, {}", cascade_error);
+            0
         }
     }
+}
 
-    cards
+pub fn get_cascade(
+    file_name: &str,
+    card_sequence: CardSequence,
+    rules: &CascadeRules,
+) -> Result<Cascade, String> {
+    let cards = try_get_cards(file_name, card_sequence, DuplicateNumbers::Report)?;
+
+    Cascade::try_build(&cards, rules)
 }
 
-fn get_points(cards: Vec<Card>) -> u32 {
-    let mut points = 0;
+pub fn get_duplicate_numbers_report(file_name: &str) -> Result<Vec<String>, String> {
+    let cards = try_get_cards(
+        file_name,
+        CardSequence::Contiguous,
+        DuplicateNumbers::Report,
+    )?;
+
+    let mut report = Vec::new();
 
     for card in cards {
-        let number_of_own_winning_numbers = card.get_number_of_own_winning_numbers();
+        if !card.get_duplicate_winning_numbers().is_empty() {
+            report.push(format!(
+                "Card {} has duplicate winning numbers {:?}",
+                card.get_id(),
+                card.get_duplicate_winning_numbers()
+            ));
+        }
 
-        if number_of_own_winning_numbers > 0 {
-            points += u32::pow(2, number_of_own_winning_numbers - 1);
+        if !card.get_duplicate_own_numbers().is_empty() {
+            report.push(format!(
+                "Card {} has duplicate own numbers {:?}",
+                card.get_id(),
+                card.get_duplicate_own_numbers()
+            ));
         }
     }
 
-    points
+    Ok(report)
 }
 
-fn get_number_of_scratchcards(cards: Vec<Card>) -> u32 {
-    let max_card_id = cards.last().unwrap().get_id();
-    let mut number_of_scratchcards = HashMap::new();
+fn try_get_cards(
+    file_name: &str,
+    card_sequence: CardSequence,
+    duplicate_numbers: DuplicateNumbers,
+) -> Result<Vec<Card>, String> {
+    let file_content =
+        fs::read_to_string(file_name).expect("input file should be located in input folder");
 
-    for card in &cards {
-        number_of_scratchcards.insert(card.get_id(), 1);
-    }
+    let cards = file_content
+        .split('\n')
+        .map(|file_line| file_line.trim())
+        .filter(|file_line| !file_line.is_empty())
+        .map(|file_line| Card::try_build_with_duplicate_numbers(file_line, duplicate_numbers))
+        .collect::<Result<Vec<Card>, String>>()?;
 
-    for card in &cards {
-        let card_id = card.get_id();
-        let number_of_own_winning_numbers = card.get_number_of_own_winning_numbers();
+    validate_card_ids(&cards, card_sequence)?;
 
-        for _ in 0..*number_of_scratchcards.get(&card_id).unwrap() {
-            for i in 0..number_of_own_winning_numbers {
-                let new_card_id = card_id + i + 1;
+    Ok(cards)
+}
 
-                if new_card_id <= max_card_id {
-                    *number_of_scratchcards.get_mut(&new_card_id).unwrap() += 1;
-                }
-            }
-        }
+fn get_points(cards: Vec<Card>, scoring_rule: &dyn ScoringRule) -> Result<u64, String> {
+    let mut points: u64 = 0;
+
+    for card in cards {
+        let card_points = scoring_rule
+            .try_score(card.get_number_of_own_winning_numbers())
+            .map_err(|score_error| {
+                format!("Cannot score card {}, {}", card.get_id(), score_error)
+            })?;
+
+        points = points
+            .checked_add(card_points)
+            .ok_or_else(|| String::from("Sum of points does not fit into u64"))?;
     }
 
-    number_of_scratchcards.values().sum()
+    Ok(points)
 }
 
 #[cfg(test)]
@@ -88,4 +136,323 @@ mod tests {
         let result = part_2("../input/test_input.txt");
         assert_eq!(result, 30);
     }
+
+    #[test]
+    fn test_input_cascade() {
+        let cascade = get_cascade(
+            "../input/test_input.txt",
+            CardSequence::Contiguous,
+            &CascadeRules::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            cascade.iter().collect::<Vec<_>>(),
+            vec![(1, 1), (2, 2), (3, 4), (4, 8), (5, 14), (6, 1)]
+        );
+        assert_eq!(cascade.get_number_of_copies(5), Some(14));
+        assert_eq!(cascade.get_number_of_copies(7), None);
+
+        let rules = CascadeRules::default().with_direction(Direction::Preceding);
+        let cascade =
+            get_cascade("../input/test_input.txt", CardSequence::Contiguous, &rules).unwrap();
+        assert_eq!(
+            cascade.iter().collect::<Vec<_>>(),
+            vec![(1, 6), (2, 3), (3, 2), (4, 1), (5, 1), (6, 1)]
+        );
+
+        let rules = CascadeRules::default().with_reach(Reach::Fixed(1));
+        let cascade =
+            get_cascade("../input/test_input.txt", CardSequence::Contiguous, &rules).unwrap();
+        assert_eq!(cascade.get_total(), 1 + 2 + 3 + 4 + 5 + 1);
+
+        let rules = CascadeRules::default().with_copies_per_card(2);
+        let cascade =
+            get_cascade("../input/test_input.txt", CardSequence::Contiguous, &rules).unwrap();
+        assert_eq!(cascade.get_total(), 1 + 3 + 9 + 27 + 75 + 1);
+    }
+
+    #[test]
+    fn test_wrap_around_cascade() {
+        let cards: Vec<Card> = ["Card 1: 1 | 2", "Card 2: 1 | 2", "Card 3: 1 2 3 | 1 2"]
+            .iter()
+            .map(|line| Card::try_build(line).unwrap())
+            .collect();
+
+        let cascade = Cascade::try_build(&cards, &CascadeRules::default()).unwrap();
+        assert_eq!(cascade.get_total(), 3);
+
+        let rules = CascadeRules::default().with_wrap_around(true);
+        let cascade = Cascade::try_build(&cards, &rules).unwrap();
+        assert_eq!(
+            cascade.iter().collect::<Vec<_>>(),
+            vec![(1, 2), (2, 2), (3, 1)]
+        );
+
+        // a card never wins copies of itself, even when its reach covers the whole pile
+        let rules = CascadeRules::default()
+            .with_reach(Reach::Fixed(10))
+            .with_wrap_around(true);
+        let cascade = Cascade::try_build(&cards, &rules).unwrap();
+        assert_eq!(
+            cascade.iter().collect::<Vec<_>>(),
+            vec![(1, 2), (2, 2), (3, 1)]
+        );
+    }
+
+    #[test]
+    fn test_input_scoring_rules() {
+        let file_name = "../input/test_input.txt";
+
+        // the test cards have 4, 2, 2, 1, 0 and 0 matches
+        assert_eq!(part_1_with_scoring_rule(file_name, &Linear::new(3)), 27);
+        assert_eq!(
+            part_1_with_scoring_rule(file_name, &Fibonacci),
+            3 + 1 + 1 + 1
+        );
+        assert_eq!(
+            part_1_with_scoring_rule(file_name, &Table::new(vec![0, 1, 10, 100, 1000])),
+            1000 + 10 + 10 + 1
+        );
+        assert_eq!(
+            get_points(
+                try_get_cards(
+                    file_name,
+                    CardSequence::Contiguous,
+                    DuplicateNumbers::Report
+                )
+                .unwrap(),
+                &Table::new(vec![0, 1, 10])
+            ),
+            Err(String::from(
+                "Cannot score card 1, Scoring table has no entry for 4 matches"
+            ))
+        );
+
+        assert_eq!(Doubling.try_score(64), Ok(1 << 63));
+        assert!(Doubling.try_score(65).is_err());
+        assert_eq!(Fibonacci.try_score(93), Ok(12200160415121876738));
+        assert!(Fibonacci.try_score(94).is_err());
+
+        let scoring_rule = try_parse_scoring_rule("table:0,1,3,6,10").unwrap();
+        assert_eq!(
+            part_1_with_scoring_rule(file_name, scoring_rule.as_ref()),
+            10 + 3 + 3 + 1
+        );
+        assert!(try_parse_scoring_rule("linear:x").is_err());
+        assert!(try_parse_scoring_rule("squares").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_numbers() {
+        let card = Card::try_build("Card 7: 5 3 5 1 | 3 3 9 1 5 5 5").unwrap();
+        assert_eq!(card.get_winning_numbers(), &[1, 3, 5]);
+        assert_eq!(card.get_matching_numbers(), &[1, 3, 5]);
+        assert_eq!(card.get_number_of_own_winning_numbers(), 3);
+        assert_eq!(card.get_duplicate_winning_numbers(), &[5]);
+        assert_eq!(card.get_duplicate_own_numbers(), &[3, 5]);
+
+        assert_eq!(
+            Card::try_build_with_duplicate_numbers(
+                "Card 7: 5 3 5 1 | 3 9",
+                DuplicateNumbers::Reject
+            )
+            .unwrap_err(),
+            "Card 7 has duplicate winning numbers [5]"
+        );
+        assert!(Card::try_build_with_duplicate_numbers(
+            "Card 7: 5 3 1 | 3 9",
+            DuplicateNumbers::Reject
+        )
+        .is_ok());
+
+        assert!(Card::try_build("Card 7: 5 3 1 3 9").is_err());
+        assert!(Card::try_build("Card x: 5 3 1 | 3 9").is_err());
+        assert!(Card::try_build("Card 7: 5 -3 1 | 3 9").is_err());
+
+        assert_eq!(
+            get_duplicate_numbers_report("../input/test_input.txt"),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_card_sequence() {
+        let get_cards = |card_ids: &[u32]| -> Vec<Card> {
+            card_ids
+                .iter()
+                .map(|card_id| Card::try_build(&format!("Card {}: 1 2 | 1 2", card_id)).unwrap())
+                .collect()
+        };
+
+        assert!(validate_card_ids(&get_cards(&[1, 2, 3]), CardSequence::Contiguous).is_ok());
+        assert_eq!(
+            validate_card_ids(&get_cards(&[2, 3]), CardSequence::Contiguous),
+            Err(String::from("Card 1 is missing, the lowest card is card 2"))
+        );
+        assert_eq!(
+            validate_card_ids(&get_cards(&[5, 4]), CardSequence::Contiguous),
+            Err(String::from(
+                "Cards 1 to 3 are missing, the lowest card is card 4"
+            ))
+        );
+        assert_eq!(
+            validate_card_ids(&get_cards(&[1, 2, 2]), CardSequence::Contiguous),
+            Err(String::from("Card 2 appears more than once"))
+        );
+        assert_eq!(
+            validate_card_ids(&get_cards(&[1, 2, 3, 1]), CardSequence::Contiguous),
+            Err(String::from("Card 1 appears more than once"))
+        );
+        assert_eq!(
+            validate_card_ids(&get_cards(&[1, 2, 3, 2]), CardSequence::Contiguous),
+            Err(String::from("Card 2 appears more than once"))
+        );
+        assert_eq!(
+            validate_card_ids(&get_cards(&[1, 3, 2]), CardSequence::Contiguous),
+            Err(String::from(
+                "Card 3 is out of order, it comes before card 2"
+            ))
+        );
+        assert_eq!(
+            validate_card_ids(&get_cards(&[2, 1, 3]), CardSequence::Contiguous),
+            Err(String::from(
+                "Card 2 is out of order, it comes before card 1"
+            ))
+        );
+        assert_eq!(
+            validate_card_ids(&get_cards(&[1, 4, 2]), CardSequence::Contiguous),
+            Err(String::from("Card 3 is missing between card 2 and card 4"))
+        );
+        assert_eq!(
+            validate_card_ids(&get_cards(&[1, 2, 6]), CardSequence::Contiguous),
+            Err(String::from(
+                "Cards 3 to 5 are missing between card 2 and card 6"
+            ))
+        );
+        assert_eq!(
+            validate_card_ids(&get_cards(&[1, 2, 0]), CardSequence::Contiguous),
+            Err(String::from("Card id 0 is invalid, card ids start at 1"))
+        );
+        assert!(validate_card_ids(&get_cards(&[7, 3, 5]), CardSequence::Sparse).is_ok());
+        assert_eq!(
+            validate_card_ids(&get_cards(&[7, 3, 7]), CardSequence::Sparse),
+            Err(String::from("Card 7 appears more than once"))
+        );
+    }
+
+    #[test]
+    fn test_sparse_cascade() {
+        // the test cards without card 3, shuffled
+        let cards: Vec<Card> = fs::read_to_string("../input/test_input.txt")
+            .unwrap()
+            .lines()
+            .map(|line| Card::try_build(line).unwrap())
+            .filter(|card| card.get_id() != 3)
+            .rev()
+            .collect();
+
+        let cascade = Cascade::try_build(&cards, &CascadeRules::default()).unwrap();
+        assert_eq!(
+            cascade.iter().collect::<Vec<_>>(),
+            vec![(1, 1), (2, 2), (4, 4), (5, 6), (6, 1)]
+        );
+
+        let rules = CascadeRules::default().with_direction(Direction::Preceding);
+        let cascade = Cascade::try_build(&cards, &rules).unwrap();
+        assert_eq!(
+            cascade.iter().collect::<Vec<_>>(),
+            vec![(1, 2), (2, 1), (4, 1), (5, 1), (6, 1)]
+        );
+
+        let mut cards = cards;
+        cards.push(Card::try_build("Card 5: 1 | 1").unwrap());
+        assert_eq!(
+            Cascade::try_build(&cards, &CascadeRules::default()),
+            Err(String::from(
+                "Cannot build cascade, card 5 appears more than once"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_input_win_graph() {
+        let cascade = get_cascade(
+            "../input/test_input.txt",
+            CardSequence::Contiguous,
+            &CascadeRules::default(),
+        )
+        .unwrap();
+
+        let wins: Vec<(u32, u32, u64)> = cascade
+            .get_wins()
+            .iter()
+            .map(|win| {
+                (
+                    win.get_card_id(),
+                    win.get_won_card_id(),
+                    win.get_number_of_copies(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            wins,
+            vec![
+                (1, 2, 1),
+                (1, 3, 1),
+                (1, 4, 1),
+                (1, 5, 1),
+                (2, 3, 2),
+                (2, 4, 2),
+                (3, 4, 4),
+                (3, 5, 4),
+                (4, 5, 8)
+            ]
+        );
+        assert_eq!(cascade.get_number_of_won_copies(5), Some(13));
+
+        // the copies won of each card add up to its won copies
+        for (card_id, number_of_copies) in cascade.iter() {
+            let won_copies: u64 = cascade
+                .get_wins()
+                .iter()
+                .filter(|win| win.get_won_card_id() == card_id)
+                .map(|win| win.get_number_of_copies())
+                .sum();
+            assert_eq!(won_copies, number_of_copies - 1);
+        }
+
+        let dot = cascade.to_dot();
+        assert!(dot.starts_with("digraph cascade {\n"));
+        assert!(dot.contains("    c5 [shape=box, label=\"Card 5\\n1 original, 13 won\"];\n"));
+        assert!(dot.contains("    c4 -> c5 [label=\"8\"];\n"));
+
+        assert_eq!(
+            cascade.to_table(),
+            concat!(
+                "Card  Original  Won  Total\n",
+                "   1         1    0      1\n",
+                "   2         1    1      2\n",
+                "   3         1    3      4\n",
+                "   4         1    7      8\n",
+                "   5         1   13     14\n",
+                "   6         1    0      1\n"
+            )
+        );
+
+        let rules = CascadeRules::default().with_direction(Direction::Preceding);
+        let cascade =
+            get_cascade("../input/test_input.txt", CardSequence::Contiguous, &rules).unwrap();
+        let wins: Vec<(u32, u32, u64)> = cascade
+            .get_wins()
+            .iter()
+            .map(|win| {
+                (
+                    win.get_card_id(),
+                    win.get_won_card_id(),
+                    win.get_number_of_copies(),
+                )
+            })
+            .collect();
+        assert_eq!(wins, vec![(2, 1, 3), (3, 1, 2), (3, 2, 2), (4, 3, 1)]);
+    }
 }
@@ -1,7 +1,79 @@
-use advent_of_code_2023_day_04::{part_1, part_2};
+use advent_of_code_2023_day_04::{
+    get_cascade, get_duplicate_numbers_report, part_1_with_scoring_rule, part_2,
+    try_parse_scoring_rule, CardSequence, CascadeRules, Doubling, ScoringRule,
+};
+use std::env;
 
 fn main() {
-    println!("Result of part 1: {}", part_1("../input/input.txt"));
+    let args: Vec<String> = env::args().collect();
+
+    let mut scoring_rule: Box<dyn ScoringRule> = Box::new(Doubling);
+    let mut print_duplicates = false;
+    let mut print_dot = false;
+    let mut print_table = false;
+
+    let mut arg_index = 1;
+    while let Some(arg) = args.get(arg_index) {
+        match (arg.as_str(), args.get(arg_index + 1)) {
+            ("--scoring", Some(scoring_rule_input)) => {
+                match try_parse_scoring_rule(scoring_rule_input) {
+                    Ok(parsed_scoring_rule) => scoring_rule = parsed_scoring_rule,
+                    Err(scoring_rule_error) => {
+                        println!("Cannot get scoring rule, {}", scoring_rule_error);
+                        return;
+                    }
+                }
+                arg_index += 1;
+            }
+            ("--duplicates", _) => print_duplicates = true,
+            ("--dot", _) => print_dot = true,
+            ("--table", _) => print_table = true,
+            _ => {
+                println!(
+                    "Usage: {} [--scoring <doubling | linear[:<points>] | fibonacci | table:<points>,...>] [--duplicates] [--dot] [--table]",
+                    args[0]
+                );
+                return;
+            }
+        }
+
+        arg_index += 1;
+    }
+
+    if print_duplicates {
+        match get_duplicate_numbers_report("../input/input.txt") {
+            Ok(report) => {
+                for line in report {
+                    println!("{}", line);
+                }
+            }
+            Err(report_error) => println!("Cannot get duplicate numbers report, {}", report_error),
+        }
+    }
+
+    if print_dot || print_table {
+        match get_cascade(
+            "../input/input.txt",
+            CardSequence::Contiguous,
+            &CascadeRules::default(),
+        ) {
+            Ok(cascade) => {
+                if print_dot {
+                    print!("{}", cascade.to_dot());
+                }
+
+                if print_table {
+                    print!("{}", cascade.to_table());
+                }
+            }
+            Err(cascade_error) => println!("Cannot get cascade, {}", cascade_error),
+        }
+    }
+
+    println!(
+        "Result of part 1: {}",
+        part_1_with_scoring_rule("../input/input.txt", scoring_rule.as_ref())
+    );
 
     println!("Result of part 2: {}", part_2("../input/input.txt"));
 }
@@ -0,0 +1,136 @@
+pub trait ScoringRule {
+    fn try_score(&self, number_of_matches: u32) -> Result<u64, String>;
+}
+
+// the first match is worth one point, every further match doubles the points
+pub struct Doubling;
+
+impl ScoringRule for Doubling {
+    fn try_score(&self, number_of_matches: u32) -> Result<u64, String> {
+        match number_of_matches {
+            0 => Ok(0),
+            number_of_matches => 1u64.checked_shl(number_of_matches - 1).ok_or_else(|| {
+                format!(
+                    "Doubling score for {} matches does not fit into u64",
+                    number_of_matches
+                )
+            }),
+        }
+    }
+}
+
+pub struct Linear {
+    points_per_match: u64,
+}
+
+impl Linear {
+    pub fn new(points_per_match: u64) -> Self {
+        Linear { points_per_match }
+    }
+}
+
+impl ScoringRule for Linear {
+    fn try_score(&self, number_of_matches: u32) -> Result<u64, String> {
+        self.points_per_match
+            .checked_mul(number_of_matches as u64)
+            .ok_or_else(|| {
+                format!(
+                    "Linear score for {} matches does not fit into u64",
+                    number_of_matches
+                )
+            })
+    }
+}
+
+// the points follow the fibonacci sequence 1, 1, 2, 3, 5, ... with no points for no matches
+pub struct Fibonacci;
+
+impl ScoringRule for Fibonacci {
+    fn try_score(&self, number_of_matches: u32) -> Result<u64, String> {
+        if number_of_matches == 0 {
+            return Ok(0);
+        }
+
+        let mut points: (u64, u64) = (0, 1);
+
+        for _ in 1..number_of_matches {
+            points = (
+                points.1,
+                points.0.checked_add(points.1).ok_or_else(|| {
+                    format!(
+                        "Fibonacci score for {} matches does not fit into u64",
+                        number_of_matches
+                    )
+                })?,
+            );
+        }
+
+        Ok(points.1)
+    }
+}
+
+// the points for n matches are the n-th entry, starting with the points for no matches
+pub struct Table {
+    points: Vec<u64>,
+}
+
+impl Table {
+    pub fn new(points: Vec<u64>) -> Self {
+        Table { points }
+    }
+}
+
+impl ScoringRule for Table {
+    fn try_score(&self, number_of_matches: u32) -> Result<u64, String> {
+        self.points
+            .get(number_of_matches as usize)
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "Scoring table has no entry for {} matches",
+                    number_of_matches
+                )
+            })
+    }
+}
+
+pub fn try_parse_scoring_rule(input: &str) -> Result<Box<dyn ScoringRule>, String> {
+    let (name, arguments) = match input.split_once(':') {
+        Some((name, arguments)) => (name, Some(arguments)),
+        None => (input, None),
+    };
+
+    match (name, arguments) {
+        ("doubling", None) => Ok(Box::new(Doubling)),
+        ("linear", None) => Ok(Box::new(Linear::new(1))),
+        ("linear", Some(points_per_match)) => match points_per_match.trim().parse::<u64>() {
+            Ok(points_per_match) => Ok(Box::new(Linear::new(points_per_match))),
+            Err(points_error) => Err(format!(
+                "Cannot parse points per match \"{}\", {}",
+                points_per_match, points_error
+            )),
+        },
+        ("fibonacci", None) => Ok(Box::new(Fibonacci)),
+        ("table", Some(points)) => {
+            let mut table = Vec::new();
+
+            for entry in points.split(',') {
+                match entry.trim().parse::<u64>() {
+                    Ok(entry) => table.push(entry),
+                    Err(entry_error) => {
+                        return Err(format!(
+                            "Cannot parse scoring table entry \"{}\", {}",
+                            entry, entry_error
+                        ))
+                    }
+                }
+            }
+
+            Ok(Box::new(Table::new(table)))
+        }
+        _ => Err(format!(
+            "Unknown scoring rule \"{}\", expected doubling, linear[:<points>], fibonacci or table:<points>,...",
+            input
+        )),
+    }
+}
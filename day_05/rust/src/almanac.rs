@@ -1,363 +1,354 @@
-struct Ranges {
-    destination_start_range: u64,
-    source_start_range: u64,
-    range_lengths: u64,
+use super::map::{self, Map, MapValidation};
+use std::{collections::BTreeMap, ops::Range};
+
+const SEED_CATEGORY: &str = "seed";
+const LOCATION_CATEGORY: &str = "location";
+
+pub struct Almanac {
+    seeds: Vec<u64>,
+    // the maps are the edges of a directed acyclic graph over the categories
+    maps: Vec<Map>,
 }
 
-impl Ranges {
-    fn try_build(input: &str) -> Option<Self> {
-        let range_input = input.split_whitespace().collect::<Vec<&str>>();
-
-        if let (Some(destination_start_range), Some(source_start_range), Some(range_lengths)) =
-            (range_input.first(), range_input.get(1), range_input.get(2))
-        {
-            if let (Ok(destination_start_range), Ok(source_start_range), Ok(range_lengths)) = (
-                destination_start_range.parse::<u64>(),
-                source_start_range.parse::<u64>(),
-                range_lengths.parse::<u64>(),
-            ) {
-                Some(Ranges {
-                    destination_start_range,
-                    source_start_range,
-                    range_lengths,
-                })
-            } else {
-                println!(
-                    "Cannot parse ranges, at least on of the input could not parse into a number in '{}'", input
-                );
-                None
+impl Almanac {
+    pub fn try_build(input: Vec<&str>) -> Result<Self, String> {
+        let mut blocks = input
+            .split(|input_line| input_line.is_empty())
+            .filter(|block| !block.is_empty());
+
+        let seeds = match blocks.next() {
+            Some([seeds_input]) => Self::try_build_seeds(seeds_input)?,
+            _ => return Err(String::from("Cannot find seeds input")),
+        };
+
+        let mut maps: Vec<Map> = Vec::new();
+
+        for block in blocks {
+            let map = Map::try_build(block[0], &block[1..])?;
+
+            if maps.iter().any(|other_map| {
+                other_map.get_source() == map.get_source()
+                    && other_map.get_destination() == map.get_destination()
+            }) {
+                return Err(format!(
+                    "Cannot build almanac, {}-to-{} map is given more than once",
+                    map.get_source(),
+                    map.get_destination()
+                ));
             }
-        } else {
-            println!(
-                "Cannot parse ranges, did not find 3 ranges inputs in '{}'",
-                input
-            );
-            None
-        }
-    }
 
-    fn get_destination_from_source(&self, source: u64) -> Option<u64> {
-        if self.is_source_in_range(source) {
-            Some(source + self.destination_start_range - self.source_start_range)
-        } else {
-            None
+            maps.push(map);
         }
-    }
 
-    fn is_source_in_range(&self, source: u64) -> bool {
-        let range = self.source_start_range..self.source_start_range + self.range_lengths;
-        range.contains(&source)
-    }
-
-    fn get_source_from_destination(&self, destination: u64) -> Option<u64> {
-        if self.is_destination_in_range(destination) {
-            Some(destination + self.source_start_range - self.destination_start_range)
-        } else {
-            None
-        }
-    }
+        let almanac = Almanac { seeds, maps };
+        almanac.validate_categories()?;
 
-    fn is_destination_in_range(&self, destination: u64) -> bool {
-        let range = self.destination_start_range..self.destination_start_range + self.range_lengths;
-        range.contains(&destination)
+        Ok(almanac)
     }
-}
 
-struct Map {
-    ranges: Vec<Ranges>,
-}
+    fn try_build_seeds(input: &str) -> Result<Vec<u64>, String> {
+        let seeds_input = input
+            .strip_prefix("seeds:")
+            .ok_or_else(|| format!("Cannot find seeds input in '{}'", input))?;
 
-impl Map {
-    fn try_build(input: Vec<&str>) -> Option<Self> {
-        let mut ranges = Vec::new();
+        let mut seeds = Vec::new();
 
-        for input_line in input {
-            if let Some(range) = Ranges::try_build(input_line) {
-                ranges.push(range);
-            } else {
-                println!("Cannot parse map");
-                return None;
+        for seed in seeds_input.split_whitespace() {
+            match seed.parse::<u64>() {
+                Ok(seed) => seeds.push(seed),
+                Err(seed_parsing_error) => {
+                    return Err(format!(
+                        "Cannot parse seed '{}', {}",
+                        seed, seed_parsing_error
+                    ))
+                }
             }
         }
 
-        Some(Map { ranges })
+        Ok(seeds)
     }
 
-    fn get_destination_from_source(&self, source: u64) -> u64 {
-        for range in &self.ranges {
-            if let Some(destination) = range.get_destination_from_source(source) {
-                return destination;
-            }
-        }
+    // the maps must not form a cycle and must lead from seeds to locations
+    fn validate_categories(&self) -> Result<(), String> {
+        let mut number_of_incoming_maps: BTreeMap<&str, usize> = BTreeMap::new();
 
-        source
-    }
+        for map in &self.maps {
+            number_of_incoming_maps.entry(map.get_source()).or_insert(0);
+            *number_of_incoming_maps
+                .entry(map.get_destination())
+                .or_insert(0) += 1;
+        }
 
-    fn get_source_from_destination(&self, destination: u64) -> u64 {
-        for range in &self.ranges {
-            if let Some(source) = range.get_source_from_destination(destination) {
-                return source;
+        let mut sorted_categories: Vec<&str> = number_of_incoming_maps
+            .iter()
+            .filter(|(_, number_of_incoming_maps)| **number_of_incoming_maps == 0)
+            .map(|(category, _)| *category)
+            .collect();
+        let mut category_index = 0;
+
+        while let Some(category) = sorted_categories.get(category_index) {
+            for map in self.get_maps_from(category) {
+                let number_of_incoming_maps = number_of_incoming_maps
+                    .get_mut(map.get_destination())
+                    .unwrap();
+                *number_of_incoming_maps -= 1;
+
+                if *number_of_incoming_maps == 0 {
+                    sorted_categories.push(map.get_destination());
+                }
             }
+
+            category_index += 1;
         }
 
-        destination
-    }
-}
+        if sorted_categories.len() < number_of_incoming_maps.len() {
+            let cyclic_categories: Vec<&str> = number_of_incoming_maps
+                .iter()
+                .filter(|(_, number_of_incoming_maps)| **number_of_incoming_maps > 0)
+                .map(|(category, _)| *category)
+                .collect();
+
+            return Err(format!(
+                "Cannot build almanac, the maps form a cycle through {}",
+                cyclic_categories.join(", ")
+            ));
+        }
 
-struct Maps {
-    seed_to_soil_map: Map,
-    soil_to_fertilizer_map: Map,
-    fertilizer_to_water_map: Map,
-    water_to_light_map: Map,
-    light_to_temperature_map: Map,
-    temperature_to_humidity_map: Map,
-    humidity_to_location_map: Map,
-}
+        self.try_get_path(SEED_CATEGORY, LOCATION_CATEGORY)
+            .map(|_| ())
+            .map_err(|path_error| format!("Cannot build almanac, {}", path_error))
+    }
 
-impl Maps {
-    fn try_build(input: Vec<&str>) -> Option<Self> {
-        let mut seed_to_soil_map: Option<Map> = None;
-        let mut soil_to_fertilizer_map: Option<Map> = None;
-        let mut fertilizer_to_water_map: Option<Map> = None;
-        let mut water_to_light_map: Option<Map> = None;
-        let mut light_to_temperature_map: Option<Map> = None;
-        let mut temperature_to_humidity_map: Option<Map> = None;
-        let mut humidity_to_location_map: Option<Map> = None;
-
-        for (line_index, input_line) in input.iter().enumerate() {
-            match *input_line {
-                "seed-to-soil map:" => {
-                    seed_to_soil_map = Map::try_build(Self::get_map_input(&input, line_index + 1))
-                }
-                "soil-to-fertilizer map:" => {
-                    soil_to_fertilizer_map =
-                        Map::try_build(Self::get_map_input(&input, line_index + 1))
-                }
-                "fertilizer-to-water map:" => {
-                    fertilizer_to_water_map =
-                        Map::try_build(Self::get_map_input(&input, line_index + 1))
-                }
-                "water-to-light map:" => {
-                    water_to_light_map = Map::try_build(Self::get_map_input(&input, line_index + 1))
-                }
-                "light-to-temperature map:" => {
-                    light_to_temperature_map =
-                        Map::try_build(Self::get_map_input(&input, line_index + 1))
-                }
-                "temperature-to-humidity map:" => {
-                    temperature_to_humidity_map =
-                        Map::try_build(Self::get_map_input(&input, line_index + 1))
-                }
-                "humidity-to-location map:" => {
-                    humidity_to_location_map =
-                        Map::try_build(Self::get_map_input(&input, line_index + 1))
-                }
-                _ => (),
-            };
-        }
+    fn get_maps_from(&self, category: &str) -> Vec<&Map> {
+        self.maps
+            .iter()
+            .filter(|map| map.get_source() == category)
+            .collect()
+    }
 
-        if let (
-            Some(seed_to_soil_map),
-            Some(soil_to_fertilizer_map),
-            Some(fertilizer_to_water_map),
-            Some(water_to_light_map),
-            Some(light_to_temperature_map),
-            Some(temperature_to_humidity_map),
-            Some(humidity_to_location_map),
-        ) = (
-            seed_to_soil_map,
-            soil_to_fertilizer_map,
-            fertilizer_to_water_map,
-            water_to_light_map,
-            light_to_temperature_map,
-            temperature_to_humidity_map,
-            humidity_to_location_map,
-        ) {
-            Some(Maps {
-                seed_to_soil_map,
-                soil_to_fertilizer_map,
-                fertilizer_to_water_map,
-                water_to_light_map,
-                light_to_temperature_map,
-                temperature_to_humidity_map,
-                humidity_to_location_map,
-            })
-        } else {
-            println!("Cannot maps, at least one of the maps is none");
-            None
+    fn try_get_path(&self, source: &str, destination: &str) -> Result<Vec<&Map>, String> {
+        let mut paths = Vec::new();
+        self.find_paths(source, destination, &mut Vec::new(), &mut paths);
+
+        match paths.len() {
+            0 => Err(format!(
+                "there is no chain of maps from {} to {}",
+                source, destination
+            )),
+            1 => Ok(paths.pop().unwrap()),
+            _ => Err(format!(
+                "there is more than one chain of maps from {} to {}",
+                source, destination
+            )),
         }
     }
 
-    fn get_map_input<'a>(input: &'a [&str], start_index: usize) -> Vec<&'a str> {
-        let mut map_input = Vec::new();
-        let mut index = start_index;
-
-        while let Some(input_line) = input.get(index) {
-            if input_line.is_empty() {
-                break;
-            }
+    // stops after the second path, as a conversion needs exactly one
+    fn find_paths<'a>(
+        &'a self,
+        category: &str,
+        destination: &str,
+        path: &mut Vec<&'a Map>,
+        paths: &mut Vec<Vec<&'a Map>>,
+    ) {
+        if paths.len() > 1 {
+            return;
+        }
 
-            map_input.push(*input_line);
-            index += 1;
+        if category == destination {
+            paths.push(path.clone());
+            return;
         }
 
-        map_input
+        for map in self.get_maps_from(category) {
+            path.push(map);
+            self.find_paths(map.get_destination(), destination, path, paths);
+            path.pop();
+        }
     }
 
-    fn get_location_from_seed(&self, seed: u64) -> u64 {
-        let soil = self.seed_to_soil_map.get_destination_from_source(seed);
-
-        let fertilizer = self
-            .soil_to_fertilizer_map
-            .get_destination_from_source(soil);
+    pub fn get_categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = self
+            .maps
+            .iter()
+            .flat_map(|map| [map.get_source(), map.get_destination()])
+            .collect();
 
-        let water = self
-            .fertilizer_to_water_map
-            .get_destination_from_source(fertilizer);
+        categories.sort_unstable();
+        categories.dedup();
 
-        let light = self.water_to_light_map.get_destination_from_source(water);
+        categories
+    }
 
-        let temperature = self
-            .light_to_temperature_map
-            .get_destination_from_source(light);
+    pub fn get_maps(&self) -> &[Map] {
+        &self.maps
+    }
 
-        let humidity = self
-            .temperature_to_humidity_map
-            .get_destination_from_source(temperature);
+    pub fn convert(&self, source: &str, destination: &str, value: u64) -> Result<u64, String> {
+        let path = self
+            .try_get_path(source, destination)
+            .map_err(|path_error| format!("Cannot convert {}, {}", value, path_error))?;
 
-        self.humidity_to_location_map
-            .get_destination_from_source(humidity)
+        Ok(path
+            .iter()
+            .fold(value, |value, map| map.get_destination_from_source(value)))
     }
 
-    fn get_seed_from_location(&self, location: u64) -> u64 {
-        let humidity = self
-            .humidity_to_location_map
-            .get_source_from_destination(location);
+    pub fn convert_ranges(
+        &self,
+        source: &str,
+        destination: &str,
+        ranges: Vec<Range<u64>>,
+    ) -> Result<Vec<Range<u64>>, String> {
+        let path = self
+            .try_get_path(source, destination)
+            .map_err(|path_error| format!("Cannot convert ranges, {}", path_error))?;
+
+        let mut ranges = ranges;
+
+        for map in path {
+            ranges = ranges
+                .into_iter()
+                .flat_map(|range| map.get_destination_ranges_from_source_range(range))
+                .collect();
+        }
 
-        let temperature = self
-            .temperature_to_humidity_map
-            .get_source_from_destination(humidity);
+        Ok(ranges)
+    }
 
-        let light = self
-            .light_to_temperature_map
-            .get_source_from_destination(temperature);
+    pub fn try_flatten(&self, source: &str, destination: &str) -> Result<Map, String> {
+        let path = self
+            .try_get_path(source, destination)
+            .map_err(|path_error| format!("Cannot flatten maps, {}", path_error))?;
 
-        let water = self.water_to_light_map.get_source_from_destination(light);
+        match path.split_first() {
+            Some((first_map, other_maps)) => {
+                let mut flat_map = first_map.flatten();
 
-        let fertilizer = self
-            .fertilizer_to_water_map
-            .get_source_from_destination(water);
+                for map in other_maps {
+                    flat_map = flat_map.try_compose(map)?;
+                }
 
-        let soil = self
-            .soil_to_fertilizer_map
-            .get_source_from_destination(fertilizer);
+                Ok(flat_map)
+            }
+            None => Map::try_build(&format!("{}-to-{} map:", source, destination), &[]),
+        }
+    }
 
-        self.seed_to_soil_map.get_source_from_destination(soil)
+    pub fn validate(&self) -> Vec<MapValidation> {
+        self.maps.iter().map(|map| map.validate()).collect()
     }
-}
 
-pub struct Almanac {
-    seeds: Vec<u64>,
-    maps: Maps,
-}
+    // converts a destination value back to the source value it comes from, which needs every
+    // map on the way to be injective
+    pub fn convert_back(&self, source: &str, destination: &str, value: u64) -> Result<u64, String> {
+        let path = self
+            .try_get_path(source, destination)
+            .map_err(|path_error| format!("Cannot convert {} back, {}", value, path_error))?;
 
-impl Almanac {
-    pub fn try_build(input: Vec<&str>) -> Option<Self> {
-        let mut seeds: Option<Vec<u64>> = None;
+        path.iter().rev().try_fold(value, |value, map| {
+            map.try_get_source_from_destination(value)
+        })
+    }
 
-        if let Some(seeds_input) = input.first() {
-            seeds = Almanac::try_build_seeds(seeds_input);
+    // every source value that ends up in one of the destination ranges, through all maps on
+    // the way, no matter whether they are injective
+    pub fn convert_ranges_back(
+        &self,
+        source: &str,
+        destination: &str,
+        ranges: Vec<Range<u64>>,
+    ) -> Result<Vec<Range<u64>>, String> {
+        let path = self
+            .try_get_path(source, destination)
+            .map_err(|path_error| format!("Cannot convert ranges back, {}", path_error))?;
+
+        let mut ranges = map::merge_ranges(ranges);
+
+        for map in path.iter().rev() {
+            ranges = map::merge_ranges(
+                ranges
+                    .into_iter()
+                    .flat_map(|range| map.get_source_ranges_from_destination_range(range))
+                    .collect(),
+            );
         }
 
-        let maps = Maps::try_build(input[2..].to_vec());
-
-        if let (Some(seeds), Some(maps)) = (seeds, maps) {
-            Some(Almanac { seeds, maps })
-        } else {
-            println!("Cannot parse almanac");
-            None
-        }
+        Ok(ranges)
     }
 
-    fn try_build_seeds(input: &str) -> Option<Vec<u64>> {
-        let mut seeds = Vec::new();
-
-        if let Some(seeds_input) = input.split(':').last() {
-            for seed in seeds_input.split_whitespace() {
-                match seed.parse::<u64>() {
-                    Ok(seed) => seeds.push(seed),
-                    Err(seed_parsing_error) => {
-                        println!("Cannot parse seed '{}', {}", seed, seed_parsing_error);
-                        return None;
-                    }
-                }
-            }
-        } else {
-            println!("Cannot find seeds input");
-            return None;
-        }
-
-        Some(seeds)
+    pub fn get_seed_ranges_from_location_range(
+        &self,
+        location_range: Range<u64>,
+    ) -> Result<Vec<Range<u64>>, String> {
+        self.convert_ranges_back(SEED_CATEGORY, LOCATION_CATEGORY, vec![location_range])
     }
 
-    pub fn get_locations_from_seeds(&self, consider_seed_range: bool) -> Vec<u64> {
-        let mut locations = Vec::new();
+    pub fn get_location_ranges_from_seeds(
+        &self,
+        consider_seed_range: bool,
+    ) -> Result<Vec<Range<u64>>, String> {
+        self.convert_ranges(
+            SEED_CATEGORY,
+            LOCATION_CATEGORY,
+            self.get_seeds(consider_seed_range)?,
+        )
+    }
 
-        if let Some(seed_ranges) = self.get_seeds(consider_seed_range) {
-            for seed_range in seed_ranges {
-                for seed in seed_range {
-                    locations.push(self.maps.get_location_from_seed(seed));
-                }
-            }
-        } else {
-            println!("Cannot get locations from seeds");
+    // one diagram per map with the seeds converted to its source category, and one for the
+    // flattened chain from seeds to locations
+    pub fn to_band_diagrams(&self, consider_seed_range: bool) -> Result<Vec<String>, String> {
+        let seed_ranges = self.get_seeds(consider_seed_range)?;
+        let mut band_diagrams = Vec::new();
+
+        for map in &self.maps {
+            // maps off the chain of the seeds are shown without seeds
+            let map_seed_ranges = self
+                .convert_ranges(SEED_CATEGORY, map.get_source(), seed_ranges.clone())
+                .unwrap_or_default();
+            band_diagrams.push(map.to_band_diagram(&map_seed_ranges));
         }
 
-        locations
+        band_diagrams.push(
+            self.try_flatten(SEED_CATEGORY, LOCATION_CATEGORY)?
+                .to_band_diagram(&seed_ranges),
+        );
+
+        Ok(band_diagrams)
     }
 
-    fn get_seeds(&self, consider_seed_range: bool) -> Option<Vec<std::ops::Range<u64>>> {
+    fn get_seeds(&self, consider_seed_range: bool) -> Result<Vec<Range<u64>>, String> {
         if consider_seed_range {
-            if self.seeds.len() % 2 == 0 {
-                let mut seeds = Vec::new();
-
-                for i in (0..self.seeds.len()).step_by(2) {
-                    let start_seed = *self.seeds.get(i).unwrap();
-                    let length = *self.seeds.get(i + 1).unwrap();
-
-                    seeds.push(start_seed..start_seed + length);
-                }
-
-                Some(seeds)
+            if self.seeds.len().is_multiple_of(2) {
+                Ok(self
+                    .seeds
+                    .chunks(2)
+                    .map(|seed_range| seed_range[0]..seed_range[0].saturating_add(seed_range[1]))
+                    .collect())
             } else {
-                println!("Could not get seed range, number of seed input is odd");
-                None
+                Err(String::from(
+                    "Cannot get seed ranges, number of seed inputs is odd",
+                ))
             }
         } else {
-            let mut seeds = Vec::new();
-
-            for seed in &self.seeds {
-                seeds.push(*seed..*seed + 1)
-            }
-
-            Some(seeds)
+            Ok(self
+                .seeds
+                .iter()
+                .map(|seed| *seed..seed.saturating_add(1))
+                .collect())
         }
     }
 
-    pub fn get_lowest_location(&self, consider_seed_range: bool) -> u64 {
-        let mut location = 0;
-
-        if let Some(seed_ranges) = self.get_seeds(consider_seed_range) {
-            while !seed_ranges
-                .iter()
-                .any(|seed_range| seed_range.contains(&self.maps.get_seed_from_location(location)))
-            {
-                location += 1;
-            }
-        } else {
-            println!("Cannot get lowest location");
-        }
+    pub fn get_location_from_seed(&self, seed: u64) -> Result<u64, String> {
+        self.convert(SEED_CATEGORY, LOCATION_CATEGORY, seed)
+    }
 
-        location
+    pub fn get_lowest_location(&self, consider_seed_range: bool) -> Result<u64, String> {
+        self.get_location_ranges_from_seeds(consider_seed_range)?
+            .iter()
+            .filter(|location_range| !location_range.is_empty())
+            .map(|location_range| location_range.start)
+            .min()
+            .ok_or_else(|| String::from("Cannot get lowest location, there are no seeds"))
     }
 }
@@ -0,0 +1,129 @@
+use super::map::Map;
+use std::ops::Range;
+
+const NUMBER_OF_COLUMNS: u64 = 64;
+const LABELS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+// every column stands for the same number of values and shows the value at its start, so ranges
+// shorter than a column may not show up in the bands, the legend lists all of them
+pub fn to_band_diagram(map: &Map, seed_ranges: &[Range<u64>]) -> String {
+    let shifted_ranges = map.get_shifted_ranges();
+    let destination_seed_ranges: Vec<Range<u64>> = seed_ranges
+        .iter()
+        .flat_map(|seed_range| map.get_destination_ranges_from_source_range(seed_range.clone()))
+        .collect();
+
+    let end = shifted_ranges
+        .iter()
+        .flat_map(|(source_range, destination_range)| [source_range.end, destination_range.end])
+        .chain(
+            seed_ranges
+                .iter()
+                .chain(&destination_seed_ranges)
+                .map(|seed_range| seed_range.end),
+        )
+        .max()
+        .unwrap_or(0);
+    let column_width = end.div_ceil(NUMBER_OF_COLUMNS).max(1);
+    let columns: Vec<Range<u64>> = (0..NUMBER_OF_COLUMNS)
+        .map(|column| {
+            column.saturating_mul(column_width)..(column + 1).saturating_mul(column_width)
+        })
+        .collect();
+
+    let source_band: String = columns
+        .iter()
+        .map(|column| {
+            match shifted_ranges
+                .iter()
+                .position(|(source_range, _)| source_range.contains(&column.start))
+            {
+                Some(range_index) => get_label(range_index),
+                None => '.',
+            }
+        })
+        .collect();
+
+    let destination_band: String = columns
+        .iter()
+        .map(|column| {
+            let range_indices: Vec<usize> = shifted_ranges
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, destination_range))| destination_range.contains(&column.start))
+                .map(|(range_index, _)| range_index)
+                .collect();
+            let is_unchanged = !shifted_ranges
+                .iter()
+                .any(|(source_range, _)| source_range.contains(&column.start));
+
+            match (range_indices.as_slice(), is_unchanged) {
+                ([], false) => ' ',
+                ([], true) => '.',
+                ([range_index], false) => get_label(*range_index),
+                _ => '*',
+            }
+        })
+        .collect();
+
+    let mut output = format!(
+        "{}-to-{} map, one column per {} values\n",
+        map.get_source(),
+        map.get_destination(),
+        column_width
+    );
+
+    output.push_str(&format!("source      |{}|\n", source_band));
+    if !seed_ranges.is_empty() {
+        output.push_str(&format!(
+            "  seeds     |{}|\n",
+            get_seed_band(&columns, seed_ranges)
+        ));
+    }
+
+    output.push_str(&format!("destination |{}|\n", destination_band));
+    if !seed_ranges.is_empty() {
+        output.push_str(&format!(
+            "  seeds     |{}|\n",
+            get_seed_band(&columns, &destination_seed_ranges)
+        ));
+    }
+
+    for (range_index, (source_range, destination_range)) in shifted_ranges.iter().enumerate() {
+        output.push_str(&format!(
+            "  {}  {:?} -> {:?} ({:+})\n",
+            get_label(range_index),
+            source_range,
+            destination_range,
+            destination_range.start as i128 - source_range.start as i128
+        ));
+    }
+
+    output.push_str("  .  unchanged values\n");
+    if destination_band.contains('*') {
+        output.push_str("  *  values reached from more than one source value\n");
+    }
+
+    output
+}
+
+// the labels run out after 52 ranges, the legend still lists those ranges in order
+fn get_label(range_index: usize) -> char {
+    LABELS.get(range_index).map_or('#', |label| *label as char)
+}
+
+fn get_seed_band(columns: &[Range<u64>], seed_ranges: &[Range<u64>]) -> String {
+    columns
+        .iter()
+        .map(|column| {
+            if seed_ranges
+                .iter()
+                .any(|seed_range| seed_range.start < column.end && column.start < seed_range.end)
+            {
+                '^'
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
@@ -1,31 +1,59 @@
 mod almanac;
+mod diagram;
+mod map;
 
-use almanac::Almanac;
-use std::fs;
+use std::{fs, ops::Range};
 
-pub fn part_1(file_name: &str) -> u64 {
-    let almanac = try_get_almanac(file_name);
+pub use almanac::Almanac;
+pub use map::{Map, MapValidation};
 
-    if let Some(almanac) = almanac {
-        get_lowest_location_of_initial_seeds(&almanac, false)
-    } else {
-        println!("Failed to get almanac");
-        0
+pub fn part_1(file_name: &str) -> u64 {
+    match try_get_almanac(file_name).and_then(|almanac| almanac.get_lowest_location(false)) {
+        Ok(lowest_location) => lowest_location,
+        Err(almanac_error) => {
+            println!("Failed to get lowest location, {}", almanac_error);
+            0
+        }
     }
 }
 
 pub fn part_2(file_name: &str) -> u64 {
-    let almanac = try_get_almanac(file_name);
-
-    if let Some(almanac) = almanac {
-        get_lowest_location_of_initial_seeds_alternative(&almanac, true)
-    } else {
-        println!("Failed to get almanac");
-        0
+    match try_get_almanac(file_name).and_then(|almanac| almanac.get_lowest_location(true)) {
+        Ok(lowest_location) => lowest_location,
+        Err(almanac_error) => {
+            println!("Failed to get lowest location, {}", almanac_error);
+            0
+        }
     }
 }
 
-fn try_get_almanac(file_name: &str) -> Option<Almanac> {
+pub fn get_flattened_map(file_name: &str, source: &str, destination: &str) -> Result<Map, String> {
+    try_get_almanac(file_name)?.try_flatten(source, destination)
+}
+
+pub fn get_seed_ranges(
+    file_name: &str,
+    location_range: Range<u64>,
+) -> Result<Vec<Range<u64>>, String> {
+    try_get_almanac(file_name)?.get_seed_ranges_from_location_range(location_range)
+}
+
+pub fn get_band_diagrams(
+    file_name: &str,
+    consider_seed_range: bool,
+) -> Result<Vec<String>, String> {
+    try_get_almanac(file_name)?.to_band_diagrams(consider_seed_range)
+}
+
+pub fn get_validation_report(file_name: &str) -> Result<Vec<String>, String> {
+    Ok(try_get_almanac(file_name)?
+        .validate()
+        .iter()
+        .map(|map_validation| map_validation.to_string())
+        .collect())
+}
+
+fn try_get_almanac(file_name: &str) -> Result<Almanac, String> {
     let file_content =
         fs::read_to_string(file_name).expect("input file should be located in input folder");
     let file_lines: Vec<&str> = file_content
@@ -36,24 +64,6 @@ fn try_get_almanac(file_name: &str) -> Option<Almanac> {
     Almanac::try_build(file_lines)
 }
 
-fn get_lowest_location_of_initial_seeds(almanac: &Almanac, consider_seed_range: bool) -> u64 {
-    let locations = almanac.get_locations_from_seeds(consider_seed_range);
-
-    if let Some(minimum_location) = locations.iter().min() {
-        *minimum_location
-    } else {
-        println!("Failed to get minimum location");
-        0
-    }
-}
-
-fn get_lowest_location_of_initial_seeds_alternative(
-    almanac: &Almanac,
-    consider_seed_range: bool,
-) -> u64 {
-    almanac.get_lowest_location(consider_seed_range)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,4 +79,332 @@ mod tests {
         let result = part_2("../input/test_input.txt");
         assert_eq!(result, 46);
     }
+
+    #[test]
+    fn test_input_location_ranges() {
+        let almanac = try_get_almanac("../input/test_input.txt").unwrap();
+
+        let mut location_ranges = almanac.get_location_ranges_from_seeds(false).unwrap();
+        location_ranges.sort_by_key(|location_range| location_range.start);
+        assert_eq!(location_ranges, vec![35..36, 43..44, 82..83, 86..87]);
+
+        // every seed of the seed ranges ends up in one of the location ranges
+        let location_ranges = almanac.get_location_ranges_from_seeds(true).unwrap();
+        assert_eq!(
+            location_ranges
+                .iter()
+                .map(|location_range| location_range.end - location_range.start)
+                .sum::<u64>(),
+            14 + 13
+        );
+        for seed in (79..79 + 14).chain(55..55 + 13) {
+            let location = almanac.get_location_from_seed(seed).unwrap();
+            assert!(location_ranges
+                .iter()
+                .any(|location_range| location_range.contains(&location)));
+        }
+    }
+
+    #[test]
+    fn test_input_categories() {
+        let almanac = try_get_almanac("../input/test_input.txt").unwrap();
+
+        assert_eq!(
+            almanac.get_categories(),
+            vec![
+                "fertilizer",
+                "humidity",
+                "light",
+                "location",
+                "seed",
+                "soil",
+                "temperature",
+                "water"
+            ]
+        );
+        assert_eq!(almanac.convert("soil", "humidity", 81), Ok(78));
+        assert_eq!(almanac.convert("seed", "seed", 79), Ok(79));
+        assert_eq!(
+            almanac.convert("humidity", "soil", 78),
+            Err(String::from(
+                "Cannot convert 78, there is no chain of maps from humidity to soil"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_category_graph() {
+        let almanac = Almanac::try_build(vec![
+            "seeds: 1 5",
+            "",
+            "seed-to-soil map:",
+            "10 0 10",
+            "",
+            "soil-to-location map:",
+            "100 10 10",
+            "",
+            "seed-to-water map:",
+            "0 0 1",
+        ])
+        .unwrap();
+        assert_eq!(almanac.get_location_from_seed(5), Ok(105));
+        assert_eq!(almanac.convert("seed", "water", 5), Ok(5));
+
+        // a second chain from seeds to locations makes the conversion ambiguous
+        assert_eq!(
+            Almanac::try_build(vec![
+                "seeds: 1",
+                "",
+                "seed-to-soil map:",
+                "",
+                "soil-to-location map:",
+                "",
+                "seed-to-location map:",
+            ])
+            .err(),
+            Some(String::from(
+                "Cannot build almanac, there is more than one chain of maps from seed to location"
+            ))
+        );
+        assert_eq!(
+            Almanac::try_build(vec![
+                "seeds: 1",
+                "",
+                "seed-to-soil map:",
+                "",
+                "soil-to-water map:",
+                "",
+                "water-to-soil map:",
+                "",
+                "water-to-location map:",
+            ])
+            .err(),
+            Some(String::from(
+                "Cannot build almanac, the maps form a cycle through location, soil, water"
+            ))
+        );
+        assert_eq!(
+            Almanac::try_build(vec!["seeds: 1", "", "seed-to-soil map:"]).err(),
+            Some(String::from(
+                "Cannot build almanac, there is no chain of maps from seed to location"
+            ))
+        );
+        assert!(Almanac::try_build(vec![
+            "seeds: 1",
+            "",
+            "seed-to-location map:",
+            "",
+            "seed-to-location map:"
+        ])
+        .is_err());
+        assert!(Almanac::try_build(vec!["seeds: 1", "", "seed-location map:"]).is_err());
+    }
+
+    #[test]
+    fn test_input_flattened_map() {
+        let almanac = try_get_almanac("../input/test_input.txt").unwrap();
+        let flat_map = almanac.try_flatten("seed", "location").unwrap();
+
+        for seed in 0..200 {
+            assert_eq!(
+                Ok(flat_map.get_destination_from_source(seed)),
+                almanac.get_location_from_seed(seed)
+            );
+        }
+
+        let mut location_ranges = flat_map.get_destination_ranges_from_source_range(79..93);
+        location_ranges.sort_by_key(|location_range| location_range.start);
+        assert_eq!(location_ranges, vec![46..56, 60..61, 82..85]);
+
+        let soil_to_water_map = almanac.try_flatten("soil", "water").unwrap();
+        assert_eq!(
+            soil_to_water_map.to_string(),
+            concat!(
+                "soil-to-water map:\n",
+                "28 0 14\n",
+                "49 14 1\n",
+                "42 15 7\n",
+                "57 22 4\n",
+                "0 26 28\n",
+                "50 54 7\n",
+            )
+        );
+
+        assert_eq!(
+            almanac.get_maps()[0]
+                .try_compose(&almanac.get_maps()[2])
+                .err(),
+            Some(String::from(
+                "Cannot compose seed-to-soil map with fertilizer-to-water map"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_input_validation() {
+        let almanac = try_get_almanac("../input/test_input.txt").unwrap();
+
+        assert!(almanac
+            .validate()
+            .iter()
+            .all(|map_validation| map_validation.is_valid()));
+        assert_eq!(almanac.convert_back("seed", "location", 46), Ok(82));
+        assert_eq!(almanac.convert_back("soil", "humidity", 78), Ok(81));
+        for location in 0..200 {
+            let seed = almanac.convert_back("seed", "location", location).unwrap();
+            assert_eq!(almanac.get_location_from_seed(seed), Ok(location));
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        let almanac = Almanac::try_build(vec![
+            "seeds: 1",
+            "",
+            "seed-to-soil map:",
+            "10 0 5",
+            "12 3 4",
+            "0 20 5",
+            "",
+            "soil-to-location map:",
+            "18446744073709551610 0 10",
+        ])
+        .unwrap();
+        let validation = almanac.validate();
+
+        assert_eq!(
+            validation[0].get_overlapping_source_ranges(),
+            [(0..5, 3..7)]
+        );
+        assert!(!validation[0].is_injective());
+        assert_eq!(
+            validation[0].to_string(),
+            concat!(
+                "seed-to-soil map is not injective\n",
+                "  source ranges 0..5 and 3..7 overlap, the first one is used\n",
+                "  source values 7..20 are not covered by any range\n",
+                "  destination values 10..16 are reached from more than one source value"
+            )
+        );
+
+        // the overflowing values are not mapped, so they collide with the mapped ones
+        assert_eq!(
+            validation[1].to_string(),
+            concat!(
+                "soil-to-location map is not injective\n",
+                "  source range 0..10 overflows u64, the overflowing part is not mapped\n",
+                "  destination values 18446744073709551610..18446744073709551615 are reached from more than one source value"
+            )
+        );
+        assert_eq!(almanac.convert("soil", "location", 2), Ok(u64::MAX - 3));
+        assert_eq!(almanac.get_location_from_seed(7), Ok(7));
+        assert_eq!(
+            almanac.convert_back("seed", "location", 14),
+            Err(String::from(
+                "Cannot get source of 14 from soil-to-location map, the map is not injective"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_input_seed_ranges() {
+        let almanac = try_get_almanac("../input/test_input.txt").unwrap();
+
+        let seed_ranges = almanac.get_seed_ranges_from_location_range(40..60).unwrap();
+        for seed in 0..200 {
+            let location = almanac.get_location_from_seed(seed).unwrap();
+            assert_eq!(
+                (40..60).contains(&location),
+                seed_ranges
+                    .iter()
+                    .any(|seed_range| seed_range.contains(&seed))
+            );
+        }
+
+        // the lowest location of part 2 is reached from one of the seed ranges
+        let seed_ranges = almanac.get_seed_ranges_from_location_range(46..47).unwrap();
+        assert!(seed_ranges
+            .iter()
+            .any(|seed_range| seed_range.contains(&82)));
+    }
+
+    #[test]
+    fn test_seed_ranges_of_non_injective_map() {
+        let almanac = Almanac::try_build(vec![
+            "seeds: 1",
+            "",
+            "seed-to-soil map:",
+            "10 0 5",
+            "12 3 4",
+            "0 20 5",
+            "",
+            "soil-to-location map:",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            almanac.convert_ranges_back("seed", "soil", vec![10..16, 30..31]),
+            Ok(vec![0..7, 10..16, 30..31])
+        );
+        assert_eq!(
+            almanac.get_seed_ranges_from_location_range(12..14),
+            Ok(vec![2..4, 12..14])
+        );
+        assert_eq!(
+            almanac
+                .convert_ranges_back("soil", "seed", vec![0..1, 5..6])
+                .err(),
+            Some(String::from(
+                "Cannot convert ranges back, there is no chain of maps from soil to seed"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_input_band_diagrams() {
+        let almanac = try_get_almanac("../input/test_input.txt").unwrap();
+        let band_diagrams = almanac.to_band_diagrams(false).unwrap();
+
+        assert_eq!(band_diagrams.len(), 8);
+        assert_eq!(
+            band_diagrams[0],
+            concat!(
+                "seed-to-soil map, one column per 2 values\n",
+                "source      |.........................aaaaaaaaaaaaaaaaaaaaaaaab..............|\n",
+                "  seeds     |      ^^                   ^           ^                        |\n",
+                "destination |.........................baaaaaaaaaaaaaaaaaaaaaaaa..............|\n",
+                "  seeds     |      ^^                    ^           ^                       |\n",
+                "  a  50..98 -> 52..100 (+2)\n",
+                "  b  98..100 -> 50..52 (-48)\n",
+                "  .  unchanged values\n"
+            )
+        );
+        assert!(band_diagrams[7].starts_with("seed-to-location map, one column per 2 values\n"));
+
+        // values that are not reached stay blank, values reached more than once are starred
+        let almanac = Almanac::try_build(vec![
+            "seeds: 1",
+            "",
+            "seed-to-soil map:",
+            "10 0 5",
+            "12 3 4",
+            "0 20 5",
+            "",
+            "soil-to-location map:",
+        ])
+        .unwrap();
+        assert_eq!(
+            almanac.get_maps()[0].to_band_diagram(&[]),
+            concat!(
+                "seed-to-soil map, one column per 1 values\n",
+                "source      |aaaaabb.............ccccc.......................................|\n",
+                "destination |ccccc  ...******....     .......................................|\n",
+                "  a  0..5 -> 10..15 (+10)\n",
+                "  b  5..7 -> 14..16 (+9)\n",
+                "  c  20..25 -> 0..5 (-20)\n",
+                "  .  unchanged values\n",
+                "  *  values reached from more than one source value\n"
+            )
+        );
+    }
 }
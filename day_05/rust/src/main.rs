@@ -1,7 +1,68 @@
-use advent_of_code_2023_day_05::{part_1, part_2};
+use advent_of_code_2023_day_05::{
+    get_band_diagrams, get_flattened_map, get_seed_ranges, get_validation_report, part_1, part_2,
+};
+use std::env;
 
 fn main() {
-    println!("Result of part 1: {}", part_1("../input/input.txt"));
+    let args: Vec<String> = env::args().collect();
 
-    println!("Result of part 2: {}", part_2("../input/input.txt"));
+    match args.get(1).map(|arg| arg.as_str()) {
+        Some("--flatten") => {
+            let source = args.get(2).map_or("seed", |source| source.as_str());
+            let destination = args
+                .get(3)
+                .map_or("location", |destination| destination.as_str());
+
+            match get_flattened_map("../input/input.txt", source, destination) {
+                Ok(flat_map) => print!("{}", flat_map),
+                Err(flatten_error) => println!("Cannot flatten maps, {}", flatten_error),
+            }
+        }
+        Some("--seeds") => {
+            let location_range = match (
+                args.get(2).map(|start| start.parse::<u64>()),
+                args.get(3).map(|end| end.parse::<u64>()),
+            ) {
+                (Some(Ok(start)), Some(Ok(end))) => start..end,
+                _ => {
+                    println!("Usage: {} --seeds <start> <end>", args[0]);
+                    return;
+                }
+            };
+
+            match get_seed_ranges("../input/input.txt", location_range) {
+                Ok(seed_ranges) => {
+                    for seed_range in seed_ranges {
+                        println!("{}..{}", seed_range.start, seed_range.end);
+                    }
+                }
+                Err(seeds_error) => println!("Cannot get seed ranges, {}", seeds_error),
+            }
+        }
+        Some("--diagram") => {
+            let consider_seed_range = args.get(2).map(|arg| arg.as_str()) == Some("--seed-ranges");
+
+            match get_band_diagrams("../input/input.txt", consider_seed_range) {
+                Ok(band_diagrams) => println!("{}", band_diagrams.join("\n")),
+                Err(diagram_error) => println!("Cannot draw band diagrams, {}", diagram_error),
+            }
+        }
+        Some("--validate") => match get_validation_report("../input/input.txt") {
+            Ok(report) => {
+                for line in report {
+                    println!("{}", line);
+                }
+            }
+            Err(validation_error) => println!("Cannot validate almanac, {}", validation_error),
+        },
+        None => {
+            println!("Result of part 1: {}", part_1("../input/input.txt"));
+
+            println!("Result of part 2: {}", part_2("../input/input.txt"));
+        }
+        _ => println!(
+            "Usage: {} [--flatten [<source> <destination>] | --seeds <start> <end> | --diagram [--seed-ranges] | --validate]",
+            args[0]
+        ),
+    }
 }
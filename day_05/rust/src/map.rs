@@ -0,0 +1,582 @@
+use super::diagram;
+use std::{fmt, ops::Range};
+
+struct Ranges {
+    destination_start_range: u64,
+    source_start_range: u64,
+    range_lengths: u64,
+}
+
+impl Ranges {
+    fn try_build(input: &str) -> Result<Self, String> {
+        let range_input = input.split_whitespace().collect::<Vec<&str>>();
+
+        if let [destination_start_range, source_start_range, range_lengths] = range_input[..] {
+            if let (Ok(destination_start_range), Ok(source_start_range), Ok(range_lengths)) = (
+                destination_start_range.parse::<u64>(),
+                source_start_range.parse::<u64>(),
+                range_lengths.parse::<u64>(),
+            ) {
+                Ok(Ranges {
+                    destination_start_range,
+                    source_start_range,
+                    range_lengths,
+                })
+            } else {
+                Err(format!(
+                    "Cannot parse ranges, at least one of the inputs could not be parsed into a number in '{}'",
+                    input
+                ))
+            }
+        } else {
+            Err(format!(
+                "Cannot parse ranges, did not find 3 range inputs in '{}'",
+                input
+            ))
+        }
+    }
+
+    fn get_destination_from_source(&self, source: u64) -> Option<u64> {
+        if self.get_source_range().contains(&source) {
+            Some(source - self.source_start_range + self.destination_start_range)
+        } else {
+            None
+        }
+    }
+
+    // the values of a range that would overflow u64 are not mapped, the validation reports them
+    fn get_length(&self) -> u64 {
+        self.range_lengths
+            .min(u64::MAX - self.source_start_range)
+            .min(u64::MAX - self.destination_start_range)
+    }
+
+    fn is_overflowing(&self) -> bool {
+        self.get_length() < self.range_lengths
+    }
+
+    fn get_source_range(&self) -> Range<u64> {
+        self.source_start_range..self.source_start_range + self.get_length()
+    }
+
+    fn get_destination_range(&self) -> Range<u64> {
+        self.destination_start_range..self.destination_start_range + self.get_length()
+    }
+
+    // splits the source range into its destination range inside the ranges source range and
+    // the source ranges before and after it
+    fn split_source_range(
+        &self,
+        source_range: Range<u64>,
+    ) -> (Option<Range<u64>>, Vec<Range<u64>>) {
+        let ranges_source_range = self.get_source_range();
+
+        let before = source_range.start..source_range.end.min(ranges_source_range.start);
+        let inside = source_range.start.max(ranges_source_range.start)
+            ..source_range.end.min(ranges_source_range.end);
+        let after = source_range.start.max(ranges_source_range.end)..source_range.end;
+
+        let destination_range = if inside.is_empty() {
+            None
+        } else {
+            Some(
+                inside.start - self.source_start_range + self.destination_start_range
+                    ..inside.end - self.source_start_range + self.destination_start_range,
+            )
+        };
+
+        (
+            destination_range,
+            [before, after]
+                .into_iter()
+                .filter(|outside| !outside.is_empty())
+                .collect(),
+        )
+    }
+}
+
+pub struct Map {
+    source: String,
+    destination: String,
+    ranges: Vec<Ranges>,
+    // flat maps have sorted, non-overlapping ranges without identity ranges
+    is_flat: bool,
+}
+
+impl Map {
+    pub fn try_build(header: &str, input: &[&str]) -> Result<Self, String> {
+        let (source, destination) = Self::try_parse_header(header)?;
+
+        let mut ranges = Vec::new();
+        for input_line in input {
+            ranges.push(Ranges::try_build(input_line).map_err(|ranges_error| {
+                format!(
+                    "Cannot parse {}-to-{} map, {}",
+                    source, destination, ranges_error
+                )
+            })?);
+        }
+
+        Ok(Map {
+            source,
+            destination,
+            ranges,
+            is_flat: false,
+        })
+    }
+
+    fn try_parse_header(header: &str) -> Result<(String, String), String> {
+        let categories = header
+            .strip_suffix(" map:")
+            .ok_or_else(|| format!("Cannot parse map header '{}'", header))?;
+
+        match categories.split_once("-to-") {
+            Some((source, destination))
+                if Self::is_category(source) && Self::is_category(destination) =>
+            {
+                Ok((String::from(source), String::from(destination)))
+            }
+            _ => Err(format!(
+                "Cannot parse map header '{}', expected '<source>-to-<destination> map:'",
+                header
+            )),
+        }
+    }
+
+    fn is_category(input: &str) -> bool {
+        !input.is_empty()
+            && input
+                .chars()
+                .all(|character| character.is_alphanumeric() || character == '_')
+    }
+
+    pub fn get_source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn get_destination(&self) -> &str {
+        &self.destination
+    }
+
+    // source values outside of all ranges keep their value, the first matching range wins
+    pub fn get_destination_from_source(&self, source: u64) -> u64 {
+        if self.is_flat {
+            let range_index = self
+                .ranges
+                .partition_point(|range| range.get_source_range().end <= source);
+
+            return self
+                .ranges
+                .get(range_index)
+                .and_then(|range| range.get_destination_from_source(source))
+                .unwrap_or(source);
+        }
+
+        for range in &self.ranges {
+            if let Some(destination) = range.get_destination_from_source(source) {
+                return destination;
+            }
+        }
+
+        source
+    }
+
+    pub fn get_destination_ranges_from_source_range(
+        &self,
+        source_range: Range<u64>,
+    ) -> Vec<Range<u64>> {
+        let mut destination_ranges = Vec::new();
+        let mut unmapped_source_ranges = vec![source_range];
+
+        for range in &self.ranges {
+            let mut remaining_source_ranges = Vec::new();
+
+            for unmapped_source_range in unmapped_source_ranges {
+                let (destination_range, outside_source_ranges) =
+                    range.split_source_range(unmapped_source_range);
+
+                destination_ranges.extend(destination_range);
+                remaining_source_ranges.extend(outside_source_ranges);
+            }
+
+            unmapped_source_ranges = remaining_source_ranges;
+        }
+
+        destination_ranges.extend(unmapped_source_ranges);
+
+        destination_ranges
+    }
+
+    // every source value that ends up in the destination range, also when several source values
+    // share a destination value, the source ranges are sorted and merged
+    pub fn get_source_ranges_from_destination_range(
+        &self,
+        destination_range: Range<u64>,
+    ) -> Vec<Range<u64>> {
+        let mut source_ranges = Vec::new();
+
+        for piece in self.get_pieces() {
+            let piece_destination_range = piece.get_destination_range();
+            let start = piece_destination_range.start.max(destination_range.start);
+            let end = piece_destination_range.end.min(destination_range.end);
+
+            if start < end {
+                source_ranges.push(
+                    start - piece.destination_start_range + piece.source_start_range
+                        ..end - piece.destination_start_range + piece.source_start_range,
+                );
+            }
+        }
+
+        merge_ranges(source_ranges)
+    }
+
+    pub fn flatten(&self) -> Map {
+        Self::build_flat(
+            self.source.clone(),
+            self.destination.clone(),
+            self.get_pieces(),
+        )
+    }
+
+    // the source and destination ranges of the values this map moves, sorted by source
+    pub fn get_shifted_ranges(&self) -> Vec<(Range<u64>, Range<u64>)> {
+        self.flatten()
+            .ranges
+            .iter()
+            .map(|range| (range.get_source_range(), range.get_destination_range()))
+            .collect()
+    }
+
+    pub fn to_band_diagram(&self, seed_ranges: &[Range<u64>]) -> String {
+        diagram::to_band_diagram(self, seed_ranges)
+    }
+
+    // the resulting map converts the source of this map directly to the destination of the other
+    pub fn try_compose(&self, other: &Map) -> Result<Map, String> {
+        if self.destination != other.source {
+            return Err(format!(
+                "Cannot compose {}-to-{} map with {}-to-{} map",
+                self.source, self.destination, other.source, other.destination
+            ));
+        }
+
+        let other_pieces = other.get_pieces();
+        let mut ranges = Vec::new();
+
+        for piece in self.get_pieces() {
+            let destination_range = piece.get_destination_range();
+
+            let mut other_piece_index = other_pieces.partition_point(|other_piece| {
+                other_piece.get_source_range().end <= destination_range.start
+            });
+
+            while let Some(other_piece) = other_pieces.get(other_piece_index) {
+                let other_source_range = other_piece.get_source_range();
+                if other_source_range.start >= destination_range.end {
+                    break;
+                }
+
+                let overlap = destination_range.start.max(other_source_range.start)
+                    ..destination_range.end.min(other_source_range.end);
+
+                ranges.push(Ranges {
+                    destination_start_range: other_piece.destination_start_range
+                        + (overlap.start - other_source_range.start),
+                    source_start_range: piece.source_start_range
+                        + (overlap.start - destination_range.start),
+                    range_lengths: overlap.end - overlap.start,
+                });
+
+                other_piece_index += 1;
+            }
+        }
+
+        Ok(Self::build_flat(
+            self.source.clone(),
+            other.destination.clone(),
+            ranges,
+        ))
+    }
+
+    // sorted, non-overlapping ranges that cover every source value below u64::MAX, the values
+    // outside of all ranges are covered by identity ranges
+    fn get_pieces(&self) -> Vec<Ranges> {
+        let mut boundaries = vec![0, u64::MAX];
+        for range in &self.ranges {
+            let source_range = range.get_source_range();
+            boundaries.push(source_range.start);
+            boundaries.push(source_range.end);
+        }
+
+        boundaries.sort_unstable();
+        boundaries.dedup();
+
+        boundaries
+            .windows(2)
+            .map(|boundary| Ranges {
+                destination_start_range: self.get_destination_from_source(boundary[0]),
+                source_start_range: boundary[0],
+                range_lengths: boundary[1] - boundary[0],
+            })
+            .collect()
+    }
+
+    // expects sorted, non-overlapping ranges, drops identity ranges and merges adjacent ranges
+    // with the same shift
+    fn build_flat(source: String, destination: String, pieces: Vec<Ranges>) -> Map {
+        let mut ranges: Vec<Ranges> = Vec::new();
+
+        for piece in pieces {
+            if piece.source_start_range == piece.destination_start_range {
+                continue;
+            }
+
+            match ranges.last_mut() {
+                Some(range)
+                    if range.get_source_range().end == piece.source_start_range
+                        && range.get_destination_range().end == piece.destination_start_range =>
+                {
+                    range.range_lengths += piece.range_lengths
+                }
+                _ => ranges.push(piece),
+            }
+        }
+
+        Map {
+            source,
+            destination,
+            ranges,
+            is_flat: true,
+        }
+    }
+
+    pub fn validate(&self) -> MapValidation {
+        let mut overlapping_source_ranges = Vec::new();
+        for (range_index, range) in self.ranges.iter().enumerate() {
+            for other_range in &self.ranges[range_index + 1..] {
+                let (source_range, other_source_range) =
+                    (range.get_source_range(), other_range.get_source_range());
+
+                if source_range.start < other_source_range.end
+                    && other_source_range.start < source_range.end
+                {
+                    overlapping_source_ranges.push((source_range, other_source_range));
+                }
+            }
+        }
+
+        let overflowing_source_ranges = self
+            .ranges
+            .iter()
+            .filter(|range| range.is_overflowing())
+            .map(|range| {
+                range.source_start_range
+                    ..range.source_start_range.saturating_add(range.range_lengths)
+            })
+            .collect();
+
+        let mut source_ranges: Vec<Range<u64>> = self
+            .ranges
+            .iter()
+            .map(|range| range.get_source_range())
+            .filter(|source_range| !source_range.is_empty())
+            .collect();
+        source_ranges.sort_by_key(|source_range| source_range.start);
+
+        let mut gaps = Vec::new();
+        let mut covered_end = source_ranges
+            .first()
+            .map_or(0, |source_range| source_range.start);
+        for source_range in source_ranges {
+            if source_range.start > covered_end {
+                gaps.push(covered_end..source_range.start);
+            }
+            covered_end = covered_end.max(source_range.end);
+        }
+
+        MapValidation {
+            source: self.source.clone(),
+            destination: self.destination.clone(),
+            overlapping_source_ranges,
+            overflowing_source_ranges,
+            gaps,
+            ambiguous_destination_ranges: self.get_ambiguous_destination_ranges(),
+        }
+    }
+
+    fn get_ambiguous_destination_ranges(&self) -> Vec<Range<u64>> {
+        let mut destination_ranges: Vec<Range<u64>> = self
+            .get_pieces()
+            .iter()
+            .map(|piece| piece.get_destination_range())
+            .collect();
+        destination_ranges.sort_by_key(|destination_range| destination_range.start);
+
+        let mut ambiguous_destination_ranges: Vec<Range<u64>> = Vec::new();
+        let mut covered_end = 0;
+
+        for destination_range in destination_ranges {
+            if destination_range.start < covered_end {
+                let ambiguous_destination_range =
+                    destination_range.start..destination_range.end.min(covered_end);
+
+                match ambiguous_destination_ranges.last_mut() {
+                    Some(last_range) if last_range.end >= ambiguous_destination_range.start => {
+                        last_range.end = last_range.end.max(ambiguous_destination_range.end)
+                    }
+                    _ => ambiguous_destination_ranges.push(ambiguous_destination_range),
+                }
+            }
+
+            covered_end = covered_end.max(destination_range.end);
+        }
+
+        ambiguous_destination_ranges
+    }
+
+    // only well-defined when the map is injective
+    pub fn try_get_source_from_destination(&self, destination: u64) -> Result<u64, String> {
+        if !self.get_ambiguous_destination_ranges().is_empty() {
+            return Err(format!(
+                "Cannot get source of {} from {}-to-{} map, the map is not injective",
+                destination, self.source, self.destination
+            ));
+        }
+
+        self.get_pieces()
+            .iter()
+            .find(|piece| piece.get_destination_range().contains(&destination))
+            .map(|piece| destination - piece.destination_start_range + piece.source_start_range)
+            .ok_or_else(|| {
+                format!(
+                    "Cannot get source of {} from {}-to-{} map, no source value maps to it",
+                    destination, self.source, self.destination
+                )
+            })
+    }
+}
+
+// sorts the ranges and merges the ones that overlap or touch
+pub fn merge_ranges(mut ranges: Vec<Range<u64>>) -> Vec<Range<u64>> {
+    ranges.retain(|range| !range.is_empty());
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged_ranges: Vec<Range<u64>> = Vec::with_capacity(ranges.len());
+
+    for range in ranges {
+        match merged_ranges.last_mut() {
+            Some(last_range) if last_range.end >= range.start => {
+                last_range.end = last_range.end.max(range.end)
+            }
+            _ => merged_ranges.push(range),
+        }
+    }
+
+    merged_ranges
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MapValidation {
+    source: String,
+    destination: String,
+    overlapping_source_ranges: Vec<(Range<u64>, Range<u64>)>,
+    overflowing_source_ranges: Vec<Range<u64>>,
+    gaps: Vec<Range<u64>>,
+    // destination values reached from more than one source value
+    ambiguous_destination_ranges: Vec<Range<u64>>,
+}
+
+impl MapValidation {
+    pub fn get_overlapping_source_ranges(&self) -> &[(Range<u64>, Range<u64>)] {
+        &self.overlapping_source_ranges
+    }
+
+    pub fn get_overflowing_source_ranges(&self) -> &[Range<u64>] {
+        &self.overflowing_source_ranges
+    }
+
+    pub fn get_gaps(&self) -> &[Range<u64>] {
+        &self.gaps
+    }
+
+    pub fn get_ambiguous_destination_ranges(&self) -> &[Range<u64>] {
+        &self.ambiguous_destination_ranges
+    }
+
+    pub fn is_injective(&self) -> bool {
+        self.ambiguous_destination_ranges.is_empty()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.overlapping_source_ranges.is_empty()
+            && self.overflowing_source_ranges.is_empty()
+            && self.is_injective()
+    }
+}
+
+impl fmt::Display for MapValidation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-to-{} map is {}",
+            self.source,
+            self.destination,
+            if self.is_injective() {
+                "injective"
+            } else {
+                "not injective"
+            }
+        )?;
+
+        for (first_source_range, second_source_range) in &self.overlapping_source_ranges {
+            write!(
+                f,
+                "\n  source ranges {:?} and {:?} overlap, the first one is used",
+                first_source_range, second_source_range
+            )?;
+        }
+
+        for overflowing_source_range in &self.overflowing_source_ranges {
+            write!(
+                f,
+                "\n  source range {:?} overflows u64, the overflowing part is not mapped",
+                overflowing_source_range
+            )?;
+        }
+
+        for gap in &self.gaps {
+            write!(
+                f,
+                "\n  source values {:?} are not covered by any range",
+                gap
+            )?;
+        }
+
+        for ambiguous_destination_range in &self.ambiguous_destination_ranges {
+            write!(
+                f,
+                "\n  destination values {:?} are reached from more than one source value",
+                ambiguous_destination_range
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}-to-{} map:", self.source, self.destination)?;
+
+        for range in &self.ranges {
+            writeln!(
+                f,
+                "{} {} {}",
+                range.destination_start_range, range.source_start_range, range.range_lengths
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 use std::fs;
 use track_record::TrackRecords;
 
+#[cfg(test)]
 mod boat;
 mod track_record;
 
@@ -61,6 +62,7 @@ fn get_number_of_ways_to_beat_single_track_record(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use track_record::TrackRecord;
 
     #[test]
     fn test_input_part_1() {
@@ -73,4 +75,50 @@ mod tests {
         let result = part_2("../input/test_input.txt");
         assert_eq!(result, 71503);
     }
+
+    #[test]
+    fn test_winning_charging_times() {
+        assert_eq!(TrackRecord::build(7, 9).get_winning_charging_times(1), 2..6);
+        // charging for 10 ms or 20 ms ties the record of 200 mm
+        assert_eq!(
+            TrackRecord::build(30, 200).get_winning_charging_times(1),
+            11..20
+        );
+        assert_eq!(
+            TrackRecord::build(30, 224).get_winning_charging_times(1),
+            15..16
+        );
+        assert!(TrackRecord::build(30, 225)
+            .get_winning_charging_times(1)
+            .is_empty());
+        assert!(TrackRecord::build(30, 200)
+            .get_winning_charging_times(0)
+            .is_empty());
+        assert_eq!(
+            TrackRecord::build(71530, 940200).get_number_of_ways_to_beat_track_record(1),
+            71503
+        );
+    }
+
+    #[test]
+    fn test_winning_charging_times_against_simulation() {
+        for time in 0..60 {
+            for distance in 0..time * time / 2 + 2 {
+                for acceleration_rate in 0..4 {
+                    let track_record = TrackRecord::build(time, distance);
+
+                    assert_eq!(
+                        track_record.get_number_of_ways_to_beat_track_record(acceleration_rate),
+                        track_record.get_number_of_ways_to_beat_track_record_by_simulation(
+                            acceleration_rate
+                        ),
+                        "time {} distance {} acceleration rate {}",
+                        time,
+                        distance,
+                        acceleration_rate
+                    );
+                }
+            }
+        }
+    }
 }
@@ -1,19 +1,62 @@
-use super::boat::Boat;
+use std::ops::Range;
 
-struct TrackRecord {
+pub struct TrackRecord {
     time_in_ms: u64,
     distance_in_mm: u64,
 }
 
 impl TrackRecord {
-    fn build(time_in_ms: u64, distance_in_mm: u64) -> Self {
+    pub fn build(time_in_ms: u64, distance_in_mm: u64) -> Self {
         TrackRecord {
             time_in_ms,
             distance_in_mm,
         }
     }
 
-    fn get_number_of_ways_to_beat_track_record(&self, acceleration_rate: u64) -> u64 {
+    // charging for c ms covers acceleration_rate * c * (time - c) mm, so the record is beaten
+    // for c * (time - c) >= distance / acceleration_rate + 1, a tie with the record does not win,
+    // the winning charging times lie symmetrically around time / 2
+    pub fn get_winning_charging_times(&self, acceleration_rate: u64) -> Range<u64> {
+        if acceleration_rate == 0 {
+            return 0..0;
+        }
+
+        let time = self.time_in_ms as u128;
+        let minimum_product = (self.distance_in_mm / acceleration_rate) as u128 + 1;
+        let product = |charging_time: u128| charging_time * (time - charging_time);
+
+        let discriminant = match (time * time).checked_sub(4 * minimum_product) {
+            Some(discriminant) => discriminant,
+            None => return 0..0,
+        };
+
+        // the integer square root may be off by one from the exact root of the discriminant
+        let mut shortest_charging_time = (time - discriminant.isqrt()) / 2;
+        while product(shortest_charging_time) < minimum_product {
+            shortest_charging_time += 1;
+        }
+        while shortest_charging_time > 0 && product(shortest_charging_time - 1) >= minimum_product {
+            shortest_charging_time -= 1;
+        }
+
+        let shortest_charging_time = shortest_charging_time as u64;
+        shortest_charging_time..self.time_in_ms - shortest_charging_time + 1
+    }
+
+    pub fn get_number_of_ways_to_beat_track_record(&self, acceleration_rate: u64) -> u64 {
+        let winning_charging_times = self.get_winning_charging_times(acceleration_rate);
+
+        winning_charging_times.end - winning_charging_times.start
+    }
+
+    // tries every charging time, only used to check the closed form
+    #[cfg(test)]
+    pub fn get_number_of_ways_to_beat_track_record_by_simulation(
+        &self,
+        acceleration_rate: u64,
+    ) -> u64 {
+        use super::boat::Boat;
+
         let mut number_of_ways_to_beat_track_record = 0;
 
         let mut boat = Boat::build(acceleration_rate);
@@ -44,10 +87,8 @@ impl TrackRecords {
                 Self::try_get_record_times(record_times_input),
                 Self::try_get_record_distances(record_distances_input),
             ) {
-                match Self::try_get_track_records(record_times, record_distances) {
-                    Some(track_records) => Some(TrackRecords { track_records }),
-                    None => None,
-                }
+                Self::try_get_track_records(record_times, record_distances)
+                    .map(|track_records| TrackRecords { track_records })
             } else {
                 println!(
                     "Cannot parse track records, either time or distances could not be parsed"
@@ -90,7 +131,7 @@ impl TrackRecords {
     }
 
     fn try_get_record_times(record_times_input: &str) -> Option<Vec<u64>> {
-        if let Some(record_times_input) = record_times_input.split(':').last() {
+        if let Some(record_times_input) = record_times_input.split(':').next_back() {
             match Self::try_get_numbers(record_times_input) {
                 Some(record_times) => Some(record_times),
                 None => {
@@ -108,7 +149,7 @@ impl TrackRecords {
     }
 
     fn try_get_record_distances(record_distances_input: &str) -> Option<Vec<u64>> {
-        match record_distances_input.split(':').last() {
+        match record_distances_input.split(':').next_back() {
             Some(record_distances_input) => match Self::try_get_numbers(record_distances_input) {
                 Some(record_distances) => Some(record_distances),
                 None => {